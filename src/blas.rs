@@ -0,0 +1,254 @@
+//! Multiplication by BLAS `sgemm` and `dgemm`, which serve as a vendor-quality reference.
+//!
+//! The `blas` feature enables the pure-Rust `matrixmultiply` crate,
+//! and the `openblas` feature links the system OpenBLAS through its CBLAS interface instead.
+
+use std::convert::Infallible;
+
+use crate::{
+    semiring::Semiring,
+    seq,
+    solver::{Multiplier, Representation},
+    task::Matrix,
+};
+
+/// Values whose matrices are multiplied by a BLAS routine.
+pub trait Gemm: Semiring {
+    /// Computes `A · B` of matrices which can be multiplied.
+    fn gemm(a: &Matrix<Self>, b: &Matrix<Self>) -> Matrix<Self>;
+}
+
+impl Gemm for f32 {
+    fn gemm(a: &Matrix<Self>, b: &Matrix<Self>) -> Matrix<Self> {
+        sgemm(a, b)
+    }
+}
+
+impl Gemm for f64 {
+    fn gemm(a: &Matrix<Self>, b: &Matrix<Self>) -> Matrix<Self> {
+        dgemm(a, b)
+    }
+}
+
+/// Multiplier calling the BLAS routine of the values.
+#[derive(Debug, Clone, Copy)]
+pub struct Blas;
+impl<M: Representation<Value: Gemm>> Multiplier<M> for Blas {
+    type Error = Infallible;
+
+    fn multiply(&mut self, a: &M, b: &M) -> Result<M, Infallible> {
+        M::product(a, b, |a, b| {
+            seq::assert_multipliable(a, b);
+            Ok(M::Value::gemm(a, b))
+        })
+    }
+}
+
+#[cfg(all(feature = "blas", not(feature = "openblas")))]
+fn sgemm(a: &Matrix<f32>, b: &Matrix<f32>) -> Matrix<f32> {
+    let (m, l, n) = (a.rows(), a.cols(), b.cols());
+    let mut c = vec![0.; m * n];
+    let ([rsa, csa], [rsb, csb]) = (strides(a), strides(b));
+    // SAFETY: the strides describe the layouts of matrices of the given dimensions.
+    unsafe {
+        matrixmultiply::sgemm(
+            m,
+            l,
+            n,
+            1.,
+            a.as_slice().as_ptr(),
+            rsa,
+            csa,
+            b.as_slice().as_ptr(),
+            rsb,
+            csb,
+            0.,
+            c.as_mut_ptr(),
+            1,
+            m as isize,
+        );
+    }
+    Matrix::new(m, n, c).unwrap()
+}
+
+#[cfg(all(feature = "blas", not(feature = "openblas")))]
+fn dgemm(a: &Matrix<f64>, b: &Matrix<f64>) -> Matrix<f64> {
+    let (m, l, n) = (a.rows(), a.cols(), b.cols());
+    let mut c = vec![0.; m * n];
+    let ([rsa, csa], [rsb, csb]) = (strides(a), strides(b));
+    // SAFETY: the strides describe the layouts of matrices of the given dimensions.
+    unsafe {
+        matrixmultiply::dgemm(
+            m,
+            l,
+            n,
+            1.,
+            a.as_slice().as_ptr(),
+            rsa,
+            csa,
+            b.as_slice().as_ptr(),
+            rsb,
+            csb,
+            0.,
+            c.as_mut_ptr(),
+            1,
+            m as isize,
+        );
+    }
+    Matrix::new(m, n, c).unwrap()
+}
+
+/// Distances between consecutive entries of a column and of a row.
+#[cfg(all(feature = "blas", not(feature = "openblas")))]
+fn strides<T>(matrix: &Matrix<T>) -> [isize; 2] {
+    use crate::task::Layout;
+
+    match matrix.layout() {
+        Layout::ColumnMajor => [1, matrix.rows() as isize],
+        Layout::RowMajor => [matrix.cols() as isize, 1],
+    }
+}
+
+#[cfg(feature = "openblas")]
+fn sgemm(a: &Matrix<f32>, b: &Matrix<f32>) -> Matrix<f32> {
+    let (m, l, n) = cblas::dimensions(a, b);
+    let ([transa, lda], [transb, ldb]) = (cblas::operand(a), cblas::operand(b));
+    let mut c = vec![0.; a.rows() * b.cols()];
+    // SAFETY: the leading dimensions are those of matrices of the given dimensions and layouts.
+    unsafe {
+        cblas::cblas_sgemm(
+            cblas::COL_MAJOR,
+            transa,
+            transb,
+            m,
+            n,
+            l,
+            1.,
+            a.as_slice().as_ptr(),
+            lda,
+            b.as_slice().as_ptr(),
+            ldb,
+            0.,
+            c.as_mut_ptr(),
+            m.max(1),
+        );
+    }
+    Matrix::new(a.rows(), b.cols(), c).unwrap()
+}
+
+#[cfg(feature = "openblas")]
+fn dgemm(a: &Matrix<f64>, b: &Matrix<f64>) -> Matrix<f64> {
+    let (m, l, n) = cblas::dimensions(a, b);
+    let ([transa, lda], [transb, ldb]) = (cblas::operand(a), cblas::operand(b));
+    let mut c = vec![0.; a.rows() * b.cols()];
+    // SAFETY: the leading dimensions are those of matrices of the given dimensions and layouts.
+    unsafe {
+        cblas::cblas_dgemm(
+            cblas::COL_MAJOR,
+            transa,
+            transb,
+            m,
+            n,
+            l,
+            1.,
+            a.as_slice().as_ptr(),
+            lda,
+            b.as_slice().as_ptr(),
+            ldb,
+            0.,
+            c.as_mut_ptr(),
+            m.max(1),
+        );
+    }
+    Matrix::new(a.rows(), b.cols(), c).unwrap()
+}
+
+#[cfg(feature = "openblas")]
+mod cblas {
+    use std::ffi::c_int;
+
+    use crate::task::{Layout, Matrix};
+
+    pub const COL_MAJOR: c_int = 102;
+    const NO_TRANS: c_int = 111;
+    const TRANS: c_int = 112;
+
+    #[link(name = "openblas")]
+    extern "C" {
+        pub fn cblas_sgemm(
+            layout: c_int,
+            transa: c_int,
+            transb: c_int,
+            m: c_int,
+            n: c_int,
+            k: c_int,
+            alpha: f32,
+            a: *const f32,
+            lda: c_int,
+            b: *const f32,
+            ldb: c_int,
+            beta: f32,
+            c: *mut f32,
+            ldc: c_int,
+        );
+
+        pub fn cblas_dgemm(
+            layout: c_int,
+            transa: c_int,
+            transb: c_int,
+            m: c_int,
+            n: c_int,
+            k: c_int,
+            alpha: f64,
+            a: *const f64,
+            lda: c_int,
+            b: *const f64,
+            ldb: c_int,
+            beta: f64,
+            c: *mut f64,
+            ldc: c_int,
+        );
+    }
+
+    /// Transposition and leading dimension of a column-major operand,
+    /// where a row-major matrix is the transpose of a column-major one.
+    pub fn operand<T>(matrix: &Matrix<T>) -> [c_int; 2] {
+        let (trans, ld) = match matrix.layout() {
+            Layout::ColumnMajor => (NO_TRANS, matrix.rows()),
+            Layout::RowMajor => (TRANS, matrix.cols()),
+        };
+        let ld = c_int::try_from(ld.max(1)).expect("Dimensions should fit into a C int");
+        [trans, ld]
+    }
+
+    /// Dimensions `m`, `l` and `n` of `A · B` as C ints.
+    pub fn dimensions<T>(a: &Matrix<T>, b: &Matrix<T>) -> (c_int, c_int, c_int) {
+        let convert = |dimension: usize| {
+            c_int::try_from(dimension).expect("Dimensions should fit into a C int")
+        };
+        (convert(a.rows()), convert(a.cols()), convert(b.cols()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq::tests::assert_matches_naive, task::Layout};
+
+    #[test]
+    fn matches_naive() {
+        let dimensions = [(1, 1, 1), (3, 5, 2), (70, 130, 65)];
+        assert_matches_naive::<f64>(&dimensions, |a, b| {
+            let Ok(c) = Blas.multiply(a, b);
+            c
+        });
+        assert_matches_naive::<f64>(&dimensions, |a, b| {
+            let Ok(c) = Blas.multiply(&*a.to_layout(Layout::RowMajor), b);
+            c
+        });
+        assert_matches_naive::<f32>(&dimensions, |a, b| {
+            let Ok(c) = Blas.multiply(a, b);
+            c
+        });
+    }
+}
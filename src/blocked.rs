@@ -0,0 +1,136 @@
+//! Cache-friendly CPU multiplication.
+
+use std::convert::Infallible;
+
+use rayon::prelude::*;
+
+use crate::{
+    semiring::Semiring,
+    seq,
+    simd::Dot,
+    solver::{Multiplier, Representation},
+    task::{Layout, Matrix},
+};
+
+/// The number of rows, columns and inner indices processed together,
+/// so that a block of each operand fits in the L1 cache for 4-byte values.
+pub const BLOCK: usize = 64;
+
+/// Multiplier running on the current thread which packs the left operand's rows
+/// and computes entries block by block with [`Semiring::dot`].
+#[derive(Debug, Clone, Copy)]
+pub struct Blocked;
+impl<M: Representation> Multiplier<M> for Blocked {
+    type Error = Infallible;
+
+    fn multiply(&mut self, a: &M, b: &M) -> Result<M, Infallible> {
+        M::product(a, b, |a, b| Ok(multiply(a, b)))
+    }
+}
+
+/// Multiplier splitting the product's column blocks between threads.
+#[derive(Debug, Clone, Copy)]
+pub struct ParallelBlocked;
+impl<M: Representation> Multiplier<M> for ParallelBlocked {
+    type Error = Infallible;
+
+    fn multiply(&mut self, a: &M, b: &M) -> Result<M, Infallible> {
+        M::product(a, b, |a, b| Ok(multiply_par(a, b)))
+    }
+}
+
+pub fn multiply<T: Semiring>(a: &Matrix<T>, b: &Matrix<T>) -> Matrix<T> {
+    seq::assert_multipliable(a, b);
+    let (m, n) = (a.rows(), b.cols());
+
+    let (rows, b) = (
+        a.to_layout(Layout::RowMajor),
+        b.to_layout(Layout::ColumnMajor),
+    );
+    let mut c = vec![T::ZERO; m * n];
+    multiply_cols(T::dot(), rows.as_slice(), m, &b, 0, &mut c);
+
+    Matrix::new(m, n, c).unwrap()
+}
+
+pub fn multiply_par<T: Semiring>(a: &Matrix<T>, b: &Matrix<T>) -> Matrix<T> {
+    seq::assert_multipliable(a, b);
+    let (m, n) = (a.rows(), b.cols());
+
+    let (rows, b) = (
+        a.to_layout(Layout::RowMajor),
+        b.to_layout(Layout::ColumnMajor),
+    );
+    let dot = T::dot();
+    let mut c = vec![T::ZERO; m * n];
+    c.par_chunks_mut((m * BLOCK).max(1))
+        .enumerate()
+        .for_each(|(block, c)| multiply_cols(dot, rows.as_slice(), m, &b, block * BLOCK, c));
+
+    Matrix::new(m, n, c).unwrap()
+}
+
+/// Adds the columns of `A · B` starting with `first_col` to `c`, which holds as many as fit,
+/// where `A` has `m` rows stored in `rows` in row-major order and `B` is column-major,
+/// so that each entry of the product is a `dot` product of contiguous slices.
+fn multiply_cols<T: Semiring>(
+    dot: Dot<T>,
+    rows: &[T],
+    m: usize,
+    b: &Matrix<T>,
+    first_col: usize,
+    c: &mut [T],
+) {
+    let l = b.rows();
+    let n = c.len().checked_div(m).unwrap_or(0);
+    let cols = &b.as_slice()[first_col * l..][..n * l];
+
+    for start in (0..l).step_by(BLOCK) {
+        let end = (start + BLOCK).min(l);
+        for block_col in (0..n).step_by(BLOCK) {
+            for block_row in (0..m).step_by(BLOCK) {
+                for col in block_col..(block_col + BLOCK).min(n) {
+                    let b = &cols[col * l..][start..end];
+                    for row in block_row..(block_row + BLOCK).min(m) {
+                        let a = &rows[row * l..][start..end];
+                        let entry = &mut c[col * m + row];
+                        *entry = entry.add(dot(a, b));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seq::tests::assert_matches_naive;
+
+    #[test]
+    fn matches_naive() {
+        let dimensions = [(1, 1, 1), (3, 5, 2), (70, 130, 65), (BLOCK, BLOCK, BLOCK)];
+        assert_matches_naive::<i64>(&dimensions, multiply);
+        assert_matches_naive::<i64>(&dimensions, multiply_par);
+        assert_matches_naive::<f64>(&dimensions, multiply);
+        assert_matches_naive::<f32>(&dimensions, multiply_par);
+    }
+
+    #[test]
+    fn integers_wrap_around() {
+        // Each entry overflows `i32` many times over, so it is its exact sum truncated.
+        let a = Matrix::new(3, BLOCK + 1, vec![i32::MAX; 3 * (BLOCK + 1)]).unwrap();
+        let b = Matrix::new(BLOCK + 1, 2, (0..2 * (BLOCK as i32 + 1)).collect()).unwrap();
+        let expected = Matrix::from_fn(3, 2, |_, col| {
+            let sum: i64 = b
+                .col(col)
+                .map(|&v| i64::from(i32::MAX) * i64::from(v))
+                .sum();
+            sum as i32
+        });
+
+        assert_eq!(seq::multiply(&a, &b), expected);
+        assert_eq!(multiply(&a, &b), expected);
+        assert_eq!(multiply_par(&a, &b), expected);
+    }
+}
@@ -2,34 +2,251 @@ use std::str::FromStr;
 
 use clap::Parser;
 
+use crate::par;
+
 #[derive(Parser, Debug)]
 pub struct Cmd {
-    /// Modes in which the computation performs
-    #[arg(long, short, default_values = ["1", "2", "3", "4", "5", "6", "7", "8"])]
+    /// Modes in which the computation performs, all modes supported by the device by default
+    #[arg(long, short)]
     pub modes: Vec<Mode>,
     /// The number of matrices
     #[arg(long, short = 'N')]
     pub matrices: usize,
-    /// Square matrix dimension
-    #[arg(long, short = 'n')]
-    pub dimension: usize,
+    /// Matrix dimensions `d`, so that the `i`-th matrix is `d[i] × d[i + 1]`,
+    /// the list is repeated along the chain which is closed by the first dimension
+    #[arg(long, short = 'n', value_delimiter = ',', required = true)]
+    pub dimensions: Vec<usize>,
     #[arg(long, short)]
     pub sample: bool,
+    /// Compute products of this many consecutive matrices instead of the full cyclic ones
+    #[arg(long, short)]
+    pub window: Option<usize>,
+    /// Only compute windows which do not wrap around the end of the chain
+    #[arg(long, requires = "window")]
+    pub linear: bool,
+    /// Replace the matrix at this index with a random one, which incremental modes
+    /// apply to their structures built for the original matrices
+    #[arg(long, short)]
+    pub update: Option<usize>,
+    /// Compute only the windows starting with these matrices, in which case
+    /// single-threaded CPU and GPU modes pick the cheaper of their strategies
+    #[arg(long, value_delimiter = ',')]
+    pub only: Vec<usize>,
+    /// Keep products normalized with a separate binary exponent so that they do not overflow,
+    /// requires floating-point arithmetic
+    #[arg(long)]
+    pub normalize: bool,
+    /// Dimension at which Strassen modes stop splitting matrices into quadrants
+    #[arg(
+        long,
+        default_value_t = 128,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub cutoff: usize,
+    /// Condition number of the inverted matrices above which similarity modes
+    /// fall back to memoizing products
+    #[arg(long, default_value_t = 1e3)]
+    pub max_condition: f64,
+    /// The number of threads running CPU modes, rayon's default by default
+    #[arg(
+        long,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub threads: Option<usize>,
+    /// Pin the threads running CPU modes to these cores in turn, or to all available cores
+    /// if none are listed
+    #[arg(long, value_delimiter = ',', num_args = 0..)]
+    pub pin: Option<Vec<usize>>,
     /// The number of random indices checked in each of the result matrices
     #[arg(long, short, default_value_t = 4)]
     pub checked_indices: usize,
+    /// Type of the matrix elements
+    #[arg(long = "type", short, value_enum, default_value_t = ValueType::F32)]
+    pub value_type: ValueType,
+    /// Semiring over which the matrices are multiplied
+    #[arg(long, short = 'S', value_enum, default_value_t = SemiringType::Arithmetic)]
+    pub semiring: SemiringType,
+    /// Write the report to this file as JSON
+    #[cfg(feature = "serde")]
+    #[arg(long)]
+    pub report: Option<std::path::PathBuf>,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    F32,
+    /// Requires `cl_khr_fp64` support for GPU modes
+    F64,
+    /// Wraps on overflow
+    I32,
+    /// Wraps on overflow
+    I64,
+    /// Integers modulo 2^31 - 1, all modes should produce identical results
+    M31,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemiringType {
+    /// The usual addition and multiplication
+    Arithmetic,
+    /// Minimum and addition, e.g. for shortest cycles
+    MinPlus,
+    /// Maximum and addition, e.g. for longest cycles
+    MaxPlus,
+    /// Disjunction and conjunction for reachability, the value type is ignored
+    Boolean,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
     CpuSingleThreaded,
     CpuMultiThreaded,
+    /// Cache-blocked multiplication with vectorized dot products
+    CpuBlocked,
+    /// Cache-blocked multiplication with vectorized dot products on multiple threads
+    CpuBlockedMultiThreaded,
+    /// Cache-blocked multiplication splitting each product's column blocks between threads
+    CpuParallelBlocked,
+    /// Cache-blocked multiplication parallelized across windows, within products, or both,
+    /// depending on the number of windows, their dimensions and the number of threads
+    CpuAdaptive,
+    CpuMemoizing,
+    /// Range products from a disjoint sparse table
+    CpuRanges,
+    /// Range products from a segment tree, which is updated incrementally
+    CpuSegmentTree,
+    /// Windows transformed by similarity with inverted matrices, experimental
+    CpuSimilarity,
+    /// Strassen–Winograd multiplication down to the cache-blocked one
+    CpuStrassen,
+    /// BLAS `sgemm` or `dgemm`, requires the `blas` or `openblas` feature
+    CpuBlas,
+    /// BLAS `sgemm` or `dgemm` on multiple threads, requires the `blas` or `openblas` feature
+    CpuBlasMultiThreaded,
     GpuNaive1,
     GpuNaive2,
     GpuNaive3,
+    GpuNaive4,
+    GpuNaive5,
+    GpuNaive6,
+    GpuNaive7,
+    GpuNaive8,
     GpuMem1,
     GpuMem2,
     GpuMem3,
+    GpuMem4,
+    GpuMem5,
+    GpuMem6,
+    GpuMem7,
+    GpuMem8,
+    /// Windows transformed by similarity with matrices inverted on the host, experimental
+    GpuSimilarity,
+    /// Strassen–Winograd multiplication with quadrants summed on the host
+    /// and multiplied by the tiled kernel
+    GpuStrassen,
+}
+
+impl Mode {
+    pub const ALL: [Self; 31] = [
+        Self::CpuSingleThreaded,
+        Self::CpuMultiThreaded,
+        Self::CpuBlocked,
+        Self::CpuBlockedMultiThreaded,
+        Self::CpuParallelBlocked,
+        Self::CpuAdaptive,
+        Self::CpuMemoizing,
+        Self::CpuRanges,
+        Self::CpuSegmentTree,
+        Self::CpuSimilarity,
+        Self::CpuStrassen,
+        Self::CpuBlas,
+        Self::CpuBlasMultiThreaded,
+        Self::GpuNaive1,
+        Self::GpuNaive2,
+        Self::GpuNaive3,
+        Self::GpuNaive4,
+        Self::GpuNaive5,
+        Self::GpuNaive6,
+        Self::GpuNaive7,
+        Self::GpuNaive8,
+        Self::GpuMem1,
+        Self::GpuMem2,
+        Self::GpuMem3,
+        Self::GpuMem4,
+        Self::GpuMem5,
+        Self::GpuMem6,
+        Self::GpuMem7,
+        Self::GpuMem8,
+        Self::GpuSimilarity,
+        Self::GpuStrassen,
+    ];
+
+    /// What the mode requires of the semiring, unless every one is supported.
+    pub const fn requirement(self) -> Option<&'static str> {
+        match self {
+            Self::CpuSimilarity | Self::GpuSimilarity => {
+                Some("similarity modes require floating-point arithmetic")
+            }
+            Self::CpuStrassen | Self::GpuStrassen => {
+                Some("Strassen modes require a semiring with subtraction")
+            }
+            Self::CpuBlas | Self::CpuBlasMultiThreaded => Some(
+                "BLAS modes require floating-point arithmetic and the `blas` or `openblas` feature",
+            ),
+            _ => None,
+        }
+    }
+
+    /// The strategy and the kernel configurations for GPU modes in the order of preference,
+    /// of which the first one supported by the device is used.
+    pub const fn gpu(self) -> Option<(Strategy, &'static [par::config::Config])> {
+        use par::config::*;
+        use Strategy::*;
+
+        Some(match self {
+            Self::CpuSingleThreaded
+            | Self::CpuMultiThreaded
+            | Self::CpuBlocked
+            | Self::CpuBlockedMultiThreaded
+            | Self::CpuParallelBlocked
+            | Self::CpuAdaptive
+            | Self::CpuMemoizing
+            | Self::CpuRanges
+            | Self::CpuSegmentTree
+            | Self::CpuSimilarity
+            | Self::CpuStrassen
+            | Self::CpuBlas
+            | Self::CpuBlasMultiThreaded => return None,
+            Self::GpuNaive1 => (Direct, &[V1]),
+            Self::GpuNaive2 => (Direct, &[V2]),
+            Self::GpuNaive3 => (Direct, &[V3]),
+            Self::GpuNaive4 => (Direct, &[V4]),
+            Self::GpuNaive5 => (Direct, &[V5]),
+            Self::GpuNaive6 => (Direct, &[V6]),
+            Self::GpuNaive7 => (Direct, &[V7]),
+            Self::GpuNaive8 => (Direct, &[V8]),
+            Self::GpuMem1 => (Memoizing, &[V1]),
+            Self::GpuMem2 => (Memoizing, &[V2]),
+            Self::GpuMem3 => (Memoizing, &[V3]),
+            Self::GpuMem4 => (Memoizing, &[V4]),
+            Self::GpuMem5 => (Memoizing, &[V5]),
+            Self::GpuMem6 => (Memoizing, &[V6]),
+            Self::GpuMem7 => (Memoizing, &[V7]),
+            Self::GpuMem8 => (Memoizing, &[V8]),
+            Self::GpuSimilarity => (Similarity, &[V8, V3, V1]),
+            Self::GpuStrassen => (Strassen, &[V3, V8, V1]),
+        })
+    }
+}
+
+/// How GPU modes obtain the windows from the products computed on the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Direct,
+    Memoizing,
+    Similarity,
+    Strassen,
 }
 
 impl FromStr for Mode {
@@ -45,6 +262,29 @@ impl FromStr for Mode {
             "6" | "gm1" => Self::GpuMem1,
             "7" | "gm2" => Self::GpuMem2,
             "8" | "gm3" => Self::GpuMem3,
+            "9" | "gn4" => Self::GpuNaive4,
+            "10" | "gn5" => Self::GpuNaive5,
+            "11" | "gn6" => Self::GpuNaive6,
+            "12" | "gn7" => Self::GpuNaive7,
+            "13" | "gn8" => Self::GpuNaive8,
+            "14" | "gm4" => Self::GpuMem4,
+            "15" | "gm5" => Self::GpuMem5,
+            "16" | "gm6" => Self::GpuMem6,
+            "17" | "gm7" => Self::GpuMem7,
+            "18" | "gm8" => Self::GpuMem8,
+            "19" | "cmem" => Self::CpuMemoizing,
+            "20" | "cr" => Self::CpuRanges,
+            "21" | "cst" => Self::CpuSegmentTree,
+            "22" | "csim" => Self::CpuSimilarity,
+            "23" | "gsim" => Self::GpuSimilarity,
+            "24" | "cb" => Self::CpuBlocked,
+            "25" | "cbm" => Self::CpuBlockedMultiThreaded,
+            "26" | "cpb" => Self::CpuParallelBlocked,
+            "27" | "ca" => Self::CpuAdaptive,
+            "28" | "cs" => Self::CpuStrassen,
+            "29" | "gs" => Self::GpuStrassen,
+            "30" | "cblas" => Self::CpuBlas,
+            "31" | "cblasm" => Self::CpuBlasMultiThreaded,
             _ => return Err(format!("Unknown mode {s:?}")),
         })
     }
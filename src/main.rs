@@ -1,44 +1,139 @@
 use std::{
     collections::BTreeSet,
+    convert::{identity, Infallible},
+    f64::consts::LOG10_2,
+    fmt::{self, Display, Formatter},
+    iter, ptr,
     time::{Duration, Instant},
 };
 
-use clap::Parser;
-use cmd::Cmd;
+use blocked::{Blocked, ParallelBlocked};
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use cmd::{Cmd, SemiringType, ValueType};
 use comfy_table::Color;
 use opencl3::{
     context::Context,
     device::{get_all_devices, Device, CL_DEVICE_TYPE_GPU},
 };
-use task::{Matrix, Solution, Task};
+use par::{ExecuteError, Executor, NewExecutorError};
+use ranges::{RangeProducts, RangeQuery};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use scaled::{Float, Scaled};
+use segment_tree::SegmentTree;
+use semiring::{Boolean, MaxPlus, MinPlus, Ring, Semiring};
+use seq::Cpu;
+use similarity::{Conditioning, Invert};
+use solver::{Multiplier, Representation};
+use strassen::Strassen;
+use task::{Matrix, Shape, Solution, Task, Window};
 use tracing::{info, warn};
+use types::{Mersenne31, Scalar};
 
+#[cfg(any(feature = "blas", feature = "openblas"))]
+mod blas;
+mod blocked;
 mod cmd;
 mod par;
+mod ranges;
+mod scaled;
+mod segment_tree;
+mod semiring;
 mod seq;
+mod simd;
+mod similarity;
+mod solver;
+mod strassen;
 mod task;
 mod types;
 mod util;
 
 fn main() {
-    let Cmd {
-        modes,
-        checked_indices,
-        matrices,
-        dimension,
-        sample,
-    } = Cmd::parse();
+    let cmd = Cmd::parse();
     tracing_subscriber::fmt::init();
 
+    let floating = matches!(
+        (cmd.semiring, cmd.value_type),
+        (SemiringType::Arithmetic, ValueType::F32 | ValueType::F64)
+    );
+    if cmd.normalize && !floating {
+        Cmd::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "normalization requires floating-point arithmetic",
+            )
+            .exit()
+    }
+
     let device = pick_device().expect("There is no available GPU device");
     let context = Context::from_device(&device).expect("Failed to create context from device");
 
-    let task = if sample {
-        let a = Matrix::from_vec(vec![1., 3., 2., 4.]).unwrap();
-        let b = Matrix::from_vec(vec![5., 7., 6., 8.]).unwrap();
-        let c = Matrix::from_vec(vec![9., 11., 10., 12.]).unwrap();
+    match (cmd.semiring, cmd.value_type) {
+        (SemiringType::Arithmetic, ValueType::F32) => run_float::<f32>(&cmd, &device, context),
+        (SemiringType::Arithmetic, ValueType::F64) => run_float::<f64>(&cmd, &device, context),
+        (SemiringType::Arithmetic, ValueType::I32) => {
+            run::<i32, StrassenModes>(&cmd, &device, context)
+        }
+        (SemiringType::Arithmetic, ValueType::I64) => {
+            run::<i64, StrassenModes>(&cmd, &device, context)
+        }
+        (SemiringType::Arithmetic, ValueType::M31) => {
+            run::<Mersenne31, StrassenModes>(&cmd, &device, context)
+        }
+        (SemiringType::MinPlus, ValueType::F32) => run::<MinPlus<f32>, ()>(&cmd, &device, context),
+        (SemiringType::MinPlus, ValueType::F64) => run::<MinPlus<f64>, ()>(&cmd, &device, context),
+        (SemiringType::MinPlus, ValueType::I32) => run::<MinPlus<i32>, ()>(&cmd, &device, context),
+        (SemiringType::MinPlus, ValueType::I64) => run::<MinPlus<i64>, ()>(&cmd, &device, context),
+        (SemiringType::MaxPlus, ValueType::F32) => run::<MaxPlus<f32>, ()>(&cmd, &device, context),
+        (SemiringType::MaxPlus, ValueType::F64) => run::<MaxPlus<f64>, ()>(&cmd, &device, context),
+        (SemiringType::MaxPlus, ValueType::I32) => run::<MaxPlus<i32>, ()>(&cmd, &device, context),
+        (SemiringType::MaxPlus, ValueType::I64) => run::<MaxPlus<i64>, ()>(&cmd, &device, context),
+        (SemiringType::MinPlus | SemiringType::MaxPlus, ValueType::M31) => Cmd::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "tropical semirings require ordered values",
+            )
+            .exit(),
+        (SemiringType::Boolean, _) => run::<Boolean, ()>(&cmd, &device, context),
+    }
+}
+
+/// Modes which floating-point values support on top of the common ones.
+#[cfg(any(feature = "blas", feature = "openblas"))]
+type FloatModes = (StrassenModes, (SimilarityModes, BlasModes));
+#[cfg(not(any(feature = "blas", feature = "openblas")))]
+type FloatModes = (StrassenModes, SimilarityModes);
+
+fn run_float<T: Float>(cmd: &Cmd, device: &Device, context: Context)
+where
+    FloatModes: Extension<Matrix<T>> + Extension<Scaled<T>>,
+{
+    if cmd.normalize {
+        run_as::<T, _, FloatModes>(cmd, device, context, Scaled::<T>::new)
+    } else {
+        run::<T, FloatModes>(cmd, device, context)
+    }
+}
+
+fn run<T: Semiring, E: Extension<Matrix<T>>>(cmd: &Cmd, device: &Device, context: Context) {
+    run_as::<T, _, E>(cmd, device, context, identity::<Matrix<T>>)
+}
+
+/// Runs the common modes and those of `E` on products of matrices in the given representation.
+fn run_as<T: Semiring, P: Product<Value = T>, E: Extension<P>>(
+    cmd: &Cmd,
+    device: &Device,
+    context: Context,
+    represent: impl Fn(Matrix<T>) -> P,
+) {
+    let matrices = if cmd.sample {
+        let matrix =
+            |rows: [[u8; 2]; 2]| Matrix::from_rows(rows.map(|row| row.map(T::from_u8))).unwrap();
+        let a = matrix([[1, 2], [3, 4]]);
+        let b = matrix([[5, 6], [7, 8]]);
+        let c = matrix([[9, 10], [11, 12]]);
+        info!("Sample matrices are\n{a}\n\n{b}\n\n{c}");
 
-        Task::from_vec(vec![
+        vec![
             a.clone(),
             b.clone(),
             c.clone(),
@@ -48,32 +143,163 @@ fn main() {
             a,
             b,
             c,
-        ])
-        .unwrap()
+        ]
     } else {
-        Task::from_vec(
-            (0..matrices)
-                .map(|_| {
-                    use rand::prelude::*;
-                    let mut rng = rand::thread_rng();
-                    Matrix::from_vec(
-                        (0..dimension * dimension)
-                            .map(|_| rng.gen_range(-1. ..0.))
-                            .map(|v| -v)
-                            .collect(),
-                    )
-                    .unwrap()
-                })
-                .collect(),
-        )
-        .unwrap()
+        let dimension = |index: usize| cmd.dimensions[index % cmd.matrices % cmd.dimensions.len()];
+        (0..cmd.matrices)
+            .map(|index| {
+                let mut rng = rand::thread_rng();
+                let (rows, cols) = (dimension(index), dimension(index + 1));
+                Matrix::new(
+                    rows,
+                    cols,
+                    (0..rows * cols).map(|_| T::random(&mut rng)).collect(),
+                )
+                .unwrap()
+            })
+            .collect()
+    };
+    let task = Task::from_vec(matrices.into_iter().map(&represent).collect()).unwrap();
+
+    let unsupported = |mode: &cmd::Mode| mode.requirement().filter(|_| !E::supports(*mode));
+    let modes = if cmd.modes.is_empty() {
+        cmd::Mode::ALL
+            .into_iter()
+            .filter(|mode| {
+                mode.gpu().is_none_or(|(_, configs)| {
+                    configs.iter().any(|config| config.supports::<T>(device))
+                }) && unsupported(mode).is_none()
+            })
+            .collect()
+    } else if let Some(reason) = cmd.modes.iter().find_map(unsupported) {
+        Cmd::command()
+            .error(ErrorKind::ArgumentConflict, reason)
+            .exit()
+    } else {
+        cmd.modes.iter().copied().collect()
+    };
+
+    let n = task.matrices().len();
+    let window = match cmd.window {
+        None => Window::full(&task),
+        Some(length) if !(1..=n).contains(&length) => Cmd::command()
+            .error(
+                ErrorKind::ValueValidation,
+                format!("window length should be in 1..={n}"),
+            )
+            .exit(),
+        Some(length) if cmd.linear => Window::linear(length),
+        Some(length) => Window::cyclic(length),
     };
 
+    let update = cmd.update.map(|index| {
+        let Some(previous) = task.matrices().get(index) else {
+            Cmd::command()
+                .error(
+                    ErrorKind::ValueValidation,
+                    format!("updated index should be in 0..{n}"),
+                )
+                .exit()
+        };
+        let mut rng = rand::thread_rng();
+        let (rows, cols) = (previous.rows(), previous.cols());
+        let values = (0..rows * cols).map(|_| T::random(&mut rng)).collect();
+        (index, represent(Matrix::new(rows, cols, values).unwrap()))
+    });
+
+    let count = window.count(n);
+    if let Some(index) = cmd.only.iter().find(|&&index| index >= count) {
+        Cmd::command()
+            .error(
+                ErrorKind::ValueValidation,
+                format!("window index {index} should be in 0..{count}"),
+            )
+            .exit()
+    }
+    let only = (!cmd.only.is_empty()).then(|| cmd.only.clone());
+
+    let (pool, pinned) = thread_pool(cmd);
     let mut measurement = Measurement {
+        device: *device,
         context,
-        modes: modes.into_iter().collect(),
+        pool,
+        pinned,
+        modes,
+        window,
+        only,
+        max_condition: cmd.max_condition,
+        cutoff: cmd.cutoff,
+    };
+    let report = measurement.run::<P, E>(&task, update, cmd.checked_indices);
+    println!("{report}");
+    #[cfg(feature = "serde")]
+    if let Some(path) = &cmd.report {
+        let written = std::fs::File::create(path)
+            .map_err(serde_json::Error::io)
+            .and_then(|file| serde_json::to_writer_pretty(std::io::BufWriter::new(file), &report));
+        match written {
+            Ok(()) => info!("Report is written to {}", path.display()),
+            Err(cause) => warn!("Failed to write the report to {}: {cause}", path.display()),
+        }
+    }
+}
+
+/// Builds the pool running CPU modes along with the core of each of its threads if they are pinned.
+fn thread_pool(cmd: &Cmd) -> (ThreadPool, Option<Vec<usize>>) {
+    let threads = cmd.threads.unwrap_or_else(rayon::current_num_threads);
+    let pinned = cmd.pin.as_ref().map(|cores| {
+        let available: Vec<_> = core_affinity::get_core_ids()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|core| core.id)
+            .collect();
+        assign_cores(threads, cores, &available).unwrap_or_else(|error| {
+            Cmd::command()
+                .error(ErrorKind::ValueValidation, error)
+                .exit()
+        })
+    });
+
+    let cores = pinned.clone();
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .start_handler(move |index| {
+            if let Some(cores) = &cores {
+                let id = cores[index];
+                if !core_affinity::set_for_current(core_affinity::CoreId { id }) {
+                    warn!("Failed to pin thread {index} to core {id}");
+                }
+            }
+        })
+        .build()
+        .expect("Failed to build the thread pool");
+    (pool, pinned)
+}
+
+/// Core of each of the `threads` threads, cycling through the `requested` ones
+/// or all `available` ones if none are requested.
+fn assign_cores(
+    threads: usize,
+    requested: &[usize],
+    available: &[usize],
+) -> Result<Vec<usize>, String> {
+    if available.is_empty() {
+        return Err("pinning threads is not supported on this platform".to_owned());
+    }
+    if let Some(core) = requested.iter().find(|core| !available.contains(core)) {
+        return Err(format!(
+            "core {core} is not one of the available cores {available:?}"
+        ));
+    }
+
+    let cores = if requested.is_empty() {
+        available
+    } else {
+        requested
     };
-    measurement.run(&task, checked_indices);
+    Ok((0..threads)
+        .map(|index| cores[index % cores.len()])
+        .collect())
 }
 
 fn pick_device() -> Option<Device> {
@@ -87,118 +313,678 @@ fn pick_device() -> Option<Device> {
 }
 
 struct Measurement {
+    device: Device,
     context: Context,
+    /// Pool running CPU modes.
+    pool: ThreadPool,
+    /// Core of each thread in the pool if they are pinned.
+    pinned: Option<Vec<usize>>,
     modes: BTreeSet<cmd::Mode>,
+    window: Window,
+    /// Indices of the only windows which should be computed.
+    only: Option<Vec<usize>>,
+    /// Condition number above which similarity modes fall back to memoizing products.
+    max_condition: f64,
+    /// Dimension at which Strassen modes stop splitting matrices.
+    cutoff: usize,
 }
 impl Measurement {
-    pub fn run(&mut self, task: &Task, random_checks: usize) {
-        let Self { context, modes } = &self;
+    /// Solves the task with the `update` applied to it, if any.
+    pub fn run<P: Product, E: Extension<P>>(
+        &mut self,
+        original: &Task<P>,
+        update: Option<(usize, P)>,
+        random_checks: usize,
+    ) -> Report {
+        let Self {
+            modes,
+            window,
+            only,
+            pool,
+            pinned,
+            ..
+        } = &self;
+        let only = only.as_deref();
+        let updated = update.as_ref().map(|(index, matrix)| {
+            let mut task = original.clone();
+            task.replace(*index, matrix.clone());
+            task
+        });
+        let task = updated.as_ref().unwrap_or(original);
 
         let mut verdicts = Vec::with_capacity(modes.len());
         for mode in modes {
             info!("[{mode:?}] Running execution");
-            verdicts.push((
-                *mode,
-                match mode {
-                    cmd::Mode::CpuSingleThreaded => Some(Self::run_cpu(false, task)),
-                    cmd::Mode::CpuMultiThreaded => Some(Self::run_cpu(true, task)),
-                    cmd::Mode::GpuNaive1 => Self::run_gpu(context, false, par::config::V1, task),
-                    cmd::Mode::GpuNaive2 => Self::run_gpu(context, false, par::config::V2, task),
-                    cmd::Mode::GpuNaive3 => Self::run_gpu(context, false, par::config::V3, task),
-                    cmd::Mode::GpuMem1 => Self::run_gpu(context, true, par::config::V1, task),
-                    cmd::Mode::GpuMem2 => Self::run_gpu(context, true, par::config::V2, task),
-                    cmd::Mode::GpuMem3 => Self::run_gpu(context, true, par::config::V3, task),
-                },
-            ));
-            info!("[{mode:?}] Completed execution");
+            let verdict = match mode.gpu() {
+                _ if E::supports(*mode) => E::run(self, *mode, task),
+                Some((strategy, _)) => self.run_gpu(*mode, task, |mut executor| {
+                    use cmd::Strategy::*;
+                    match (strategy, only) {
+                        (Similarity | Strassen, _) => {
+                            unreachable!("{mode:?} is not a common mode")
+                        }
+                        (_, Some(indices)) => {
+                            Self::try_measure(|| executor.solve_only(task, *window, indices))
+                        }
+                        (Memoizing, None) => {
+                            Self::try_measure(|| executor.solve_memoizing(task, *window))
+                        }
+                        (Direct, None) => Self::try_measure(|| executor.solve(task, *window)),
+                    }
+                }),
+                // CPU modes run on the dedicated pool, whose size the adaptive mode relies on.
+                None => Ok(pool.install(|| match mode {
+                    cmd::Mode::CpuSingleThreaded => Self::run_cpu(Cpu, false, task, *window, only),
+                    cmd::Mode::CpuMultiThreaded => Self::run_cpu(Cpu, true, task, *window, only),
+                    cmd::Mode::CpuBlocked => Self::run_cpu(Blocked, false, task, *window, only),
+                    cmd::Mode::CpuBlockedMultiThreaded => {
+                        Self::run_cpu(Blocked, true, task, *window, only)
+                    }
+                    cmd::Mode::CpuParallelBlocked => {
+                        Self::run_cpu(ParallelBlocked, false, task, *window, only)
+                    }
+                    cmd::Mode::CpuAdaptive => {
+                        let windows = only.map_or(window.count(task.matrices().len()), <[_]>::len);
+                        let parallelism = seq::Parallelism::schedule(
+                            windows,
+                            task.max_dimension(),
+                            rayon::current_num_threads(),
+                        );
+                        info!("[{mode:?}] Scheduled {parallelism:?} parallelism");
+                        Self::measure(|| match only {
+                            Some(indices) => {
+                                seq::solve_scheduled_only(parallelism, task, *window, indices)
+                            }
+                            None => seq::solve_scheduled(parallelism, task, *window),
+                        })
+                    }
+                    cmd::Mode::CpuMemoizing => Self::measure(|| match only {
+                        Some(indices) => seq::solve_only(task, *window, indices),
+                        None => seq::solve_memoizing(task, *window),
+                    }),
+                    cmd::Mode::CpuRanges => {
+                        let Ok(verdict) = Self::try_measure(|| {
+                            let ranges = RangeProducts::new(&mut Cpu, task)?;
+                            match only {
+                                Some(indices) => {
+                                    ranges.solve_only(&mut Cpu, *window, indices.iter().copied())
+                                }
+                                None => ranges.solve(&mut Cpu, *window),
+                            }
+                        });
+                        verdict
+                    }
+                    cmd::Mode::CpuSegmentTree => {
+                        Self::run_segment_tree(original, update.as_ref(), *window, only)
+                    }
+                    mode => unreachable!("{mode:?} is not a common CPU mode"),
+                })),
+            };
+            match &verdict {
+                Ok(_) => info!("[{mode:?}] Completed execution"),
+                Err(cause) => warn!("[{mode:?}] Failed: {cause}"),
+            }
+            verdicts.push((*mode, verdict));
         }
 
         let random_indices = {
             use rand::prelude::*;
             let mut rng = rand::thread_rng();
+            let n = task.matrices().len();
+            let count = only.map_or(window.count(n), <[_]>::len);
             (0..random_checks)
                 .map(|_| {
+                    // Position of the window in the solution and the index it starts with.
+                    let position = rng.gen_range(0..count);
+                    let index = only.map_or(position, |indices| indices[position]);
+                    // The window starting with `A[i]` is `d[i] × d[i + length]`.
+                    let rows = task.matrices()[index].rows();
+                    let cols = task.matrices()[(index + window.length - 1) % n].cols();
                     (
-                        rng.gen_range(0..task.matrices().len()),
-                        rng.gen_range(0..task.n()),
-                        rng.gen_range(0..task.n()),
+                        position,
+                        index,
+                        rng.gen_range(0..rows),
+                        rng.gen_range(0..cols),
                     )
                 })
                 .collect::<Vec<_>>()
         };
 
-        use comfy_table::{presets::UTF8_FULL, Cell, CellAlignment, Table};
-        let mut table = Table::new();
-        table.load_preset(UTF8_FULL).set_header(
-            ["Mode", "Time"]
-                .iter()
-                .map(|s| s.to_string())
-                .chain(
-                    random_indices
-                        .iter()
-                        .map(|(index, x, y)| format!("[{index}]({x}, {y})")),
-                )
-                .collect::<Vec<_>>(),
-        );
-        for (mode, verdict) in verdicts {
-            let mode = Cell::new(format!("{mode:?}")).set_alignment(CellAlignment::Right);
-            if let Some(Verdict { solution, time }) = verdict {
-                table.add_row([mode, Cell::new(format!("{time:?}"))].into_iter().chain(
-                    random_indices.iter().map(|(index, x, y)| {
-                        Cell::new(format!("{:.6}", &solution.0[*index][*x + *y * task.n()]))
-                            .set_alignment(CellAlignment::Right)
+        // Solutions are compared against the first one obtained, which is normally a CPU one.
+        let reference = verdicts
+            .iter()
+            .find_map(|(_, verdict)| verdict.as_ref().ok())
+            .map(|verdict| &verdict.solution);
+        let rows = verdicts
+            .iter()
+            .map(|(mode, verdict)| Row {
+                mode: *mode,
+                outcome: match verdict {
+                    Ok(Verdict {
+                        solution,
+                        time,
+                        padding,
+                    }) => Ok(Outcome {
+                        time: *time,
+                        padding: *padding,
+                        deviation: reference
+                            .filter(|reference| !ptr::eq(*reference, solution))
+                            .map(|reference| deviation(solution, reference)),
+                        non_finite: match non_finite(solution) {
+                            0 => 0,
+                            count => {
+                                warn!("[{mode:?}] Solution has {count} NaN or infinite entries");
+                                count
+                            }
+                        },
+                        entries: random_indices
+                            .iter()
+                            .map(|(position, _, x, y)| {
+                                format_entry(solution.0[*position].entry((*x, *y)))
+                            })
+                            .collect(),
                     }),
-                ));
-            } else {
-                table.add_row([mode, Cell::new("-").fg(Color::Grey)]);
-            }
+                    Err(cause) => Err(cause.to_string()),
+                },
+            })
+            .collect();
+
+        Report {
+            threads: pool.current_num_threads(),
+            pinned: pinned.clone(),
+            samples: random_indices
+                .iter()
+                .map(|&(_, index, x, y)| (index, x, y))
+                .collect(),
+            rows,
         }
-        println!("{table}");
     }
 
-    fn run_cpu(parallel: bool, task: &Task) -> Verdict {
-        if parallel {
-            Self::measure(|| seq::solve_par(task))
-        } else {
-            Self::measure(|| seq::solve(task))
+    fn run_cpu<P: Clone + Shape + Send + Sync>(
+        mut cpu: impl Multiplier<P, Error = Infallible> + Copy + Send + Sync,
+        parallel: bool,
+        task: &Task<P>,
+        window: Window,
+        only: Option<&[usize]>,
+    ) -> Verdict<P> {
+        match (parallel, only) {
+            (true, None) => Self::measure(|| seq::solve_par(cpu, task, window)),
+            (true, Some(indices)) => {
+                Self::measure(|| seq::solve_par_only(cpu, task, window, indices))
+            }
+            (false, None) => {
+                let Ok(verdict) = Self::try_measure(|| solver::solve(&mut cpu, task, window));
+                verdict
+            }
+            (false, Some(indices)) => {
+                let Ok(verdict) =
+                    Self::try_measure(|| solver::solve_only(&mut cpu, task, window, indices));
+                verdict
+            }
         }
     }
 
-    fn run_gpu(
-        context: &Context,
-        memoizing: bool,
-        config: par::config::Config,
-        task: &Task,
-    ) -> Option<Verdict> {
-        let mut executor = match par::Executor::new(task.n(), context, config) {
-            Ok(executor) => executor,
-            Err(cause) => {
-                warn!("Unable to run execution: {cause}");
-                return None;
+    fn run_segment_tree<P: Clone + Shape + Representation>(
+        original: &Task<P>,
+        update: Option<&(usize, P)>,
+        window: Window,
+        only: Option<&[usize]>,
+    ) -> Verdict<P> {
+        // The tree is built beforehand, as if it was kept from solving the original task.
+        let Ok(mut tree) = SegmentTree::new(&mut Cpu, original);
+        let Ok(verdict) = Self::try_measure(|| {
+            if let Some((index, matrix)) = update {
+                tree.update(&mut Cpu, *index, matrix.clone())?;
             }
-        };
+            match only {
+                Some(indices) => tree.solve_only(&mut Cpu, window, indices.iter().copied()),
+                None => tree.solve(&mut Cpu, window),
+            }
+        });
+        verdict
+    }
 
-        Some(if memoizing {
-            Self::measure(|| executor.solve_memoizing(task))
-        } else {
-            Self::measure(|| executor.solve(task))
-        })
+    /// Creates the executor of the GPU mode, which `solve` solves the task with.
+    fn run_gpu<P: Product>(
+        &self,
+        mode: cmd::Mode,
+        task: &Task<P>,
+        solve: impl FnOnce(Executor<P::Value>) -> Result<Verdict<P>, ExecuteError>,
+    ) -> Result<Verdict<P>, ModeError> {
+        let (_, configs) = mode.gpu().expect("All other modes are CPU ones");
+        let config = configs
+            .iter()
+            .find(|config| config.supports::<P::Value>(&self.device))
+            .unwrap_or(&configs[0]);
+        let executor = Executor::new(task.max_dimension(), &self.context, *config)?;
+
+        let padding = executor.padding();
+        if padding != 0 {
+            info!("Matrices are padded with {padding} zero rows and columns on the device");
+        }
+
+        let mut verdict = solve(executor)?;
+        verdict.padding = padding;
+        Ok(verdict)
+    }
+
+    fn measure<P>(job: impl FnOnce() -> Solution<P>) -> Verdict<P> {
+        let Ok(verdict) = Self::try_measure(|| Ok::<_, Infallible>(job()));
+        verdict
     }
 
     #[inline(always)]
-    fn measure(job: impl FnOnce() -> Solution) -> Verdict {
+    fn try_measure<P, E>(job: impl FnOnce() -> Result<Solution<P>, E>) -> Result<Verdict<P>, E> {
         let begin = Instant::now();
-        let solution = job();
+        let solution = job()?;
         let end = Instant::now();
 
-        Verdict {
+        Ok(Verdict {
             solution,
             time: end - begin,
+            padding: 0,
+        })
+    }
+
+    /// Measures a similarity transformation, reporting how well-conditioned the matrices were.
+    fn try_measure_similarity<P, E>(
+        job: impl FnOnce() -> Result<(Solution<P>, Conditioning), E>,
+    ) -> Result<Verdict<P>, E> {
+        let mut conditioning = None;
+        let verdict = Self::try_measure(|| {
+            let (solution, report) = job()?;
+            conditioning = Some(report);
+            Ok(solution)
+        })?;
+
+        match conditioning.expect("The job has been run") {
+            Conditioning::Inverted(condition) => {
+                info!("Largest condition number of the inverted matrices is {condition:.2e}")
+            }
+            Conditioning::Fallback { index, condition } => warn!(
+                "Matrix {index} has condition number {condition:.2e}, falling back to memoizing products"
+            ),
         }
+        Ok(verdict)
     }
 }
 
-struct Verdict {
-    solution: Solution,
+/// Why a mode failed to solve the task.
+#[derive(thiserror::Error, Debug)]
+enum ModeError {
+    #[error("unable to create the executor: {0}")]
+    New(#[from] NewExecutorError),
+    #[error("execution failed: {0}")]
+    Execute(#[from] ExecuteError),
+}
+
+/// Outcome of the modes on a task, which is printed as a table.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Report {
+    /// The number of threads running CPU modes.
+    threads: usize,
+    /// Core of each thread running CPU modes if they are pinned.
+    pinned: Option<Vec<usize>>,
+    /// Window index, row and column of each of the sampled entries.
+    samples: Vec<(usize, usize, usize)>,
+    rows: Vec<Row>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Row {
+    mode: cmd::Mode,
+    /// The measurement or the cause of the failure.
+    outcome: Result<Outcome, String>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Outcome {
+    time: Duration,
+    /// The number of zero rows and columns added to the largest matrix to fit the device's tiles.
+    padding: usize,
+    /// How much the solution differs from the reference one, unless it is the reference.
+    deviation: Option<String>,
+    /// The number of NaN and infinite entries.
+    non_finite: usize,
+    /// The sampled entries, formatted since they may be far beyond the range of `f64`.
+    entries: Vec<String>,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use comfy_table::{presets::UTF8_FULL, Cell, CellAlignment, Table};
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL).set_header(
+            [
+                "Mode",
+                "Time",
+                "Threads",
+                "Padding",
+                "Deviation",
+                "Non-finite",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .chain(
+                self.samples
+                    .iter()
+                    .map(|(index, x, y)| format!("[{index}]({x}, {y})")),
+            )
+            .collect::<Vec<_>>(),
+        );
+        let threads = match self.pinned {
+            Some(_) => format!("{} pinned", self.threads),
+            None => self.threads.to_string(),
+        };
+        for Row { mode, outcome } in &self.rows {
+            match outcome {
+                Ok(outcome) => {
+                    table.add_row(
+                        [
+                            Cell::new(format!("{mode:?}")).set_alignment(CellAlignment::Right),
+                            Cell::new(format!("{:?}", outcome.time)),
+                            match mode.gpu() {
+                                Some(_) => Cell::new("-").fg(Color::Grey),
+                                None => Cell::new(&threads),
+                            }
+                            .set_alignment(CellAlignment::Right),
+                            Cell::new(outcome.padding).set_alignment(CellAlignment::Right),
+                            Cell::new(outcome.deviation.as_deref().unwrap_or("reference"))
+                                .set_alignment(CellAlignment::Right),
+                            match outcome.non_finite {
+                                0 => Cell::new(0),
+                                count => Cell::new(count).fg(Color::Red),
+                            }
+                            .set_alignment(CellAlignment::Right),
+                        ]
+                        .into_iter()
+                        .chain(
+                            outcome
+                                .entries
+                                .iter()
+                                .map(|entry| Cell::new(entry).set_alignment(CellAlignment::Right)),
+                        ),
+                    );
+                }
+                Err(cause) => {
+                    table.add_row([
+                        Cell::new(format!("{mode:?}")).set_alignment(CellAlignment::Right),
+                        Cell::new(cause).fg(Color::Red),
+                    ]);
+                }
+            }
+        }
+        write!(f, "{table}")?;
+        if let Some(cores) = &self.pinned {
+            write!(f, "\nCPU threads are pinned to cores {cores:?}")?;
+        }
+        Ok(())
+    }
+}
+
+struct Verdict<P> {
+    solution: Solution<P>,
     time: Duration,
+    /// The number of zero rows and columns added to the largest matrix to fit the device's tiles.
+    padding: usize,
+}
+
+/// Modes which only some semirings support, see [`cmd::Mode::requirement`].
+trait Extension<P> {
+    /// Whether the mode is one of these.
+    fn supports(mode: cmd::Mode) -> bool;
+
+    /// Solves the task in one of the supported modes.
+    fn run(
+        measurement: &Measurement,
+        mode: cmd::Mode,
+        task: &Task<P>,
+    ) -> Result<Verdict<P>, ModeError>;
+}
+
+/// No modes beyond the common ones.
+impl<P> Extension<P> for () {
+    fn supports(_: cmd::Mode) -> bool {
+        false
+    }
+
+    fn run(_: &Measurement, mode: cmd::Mode, _: &Task<P>) -> Result<Verdict<P>, ModeError> {
+        unreachable!("{mode:?} is not supported")
+    }
+}
+
+/// The modes of both extensions.
+impl<P, A: Extension<P>, B: Extension<P>> Extension<P> for (A, B) {
+    fn supports(mode: cmd::Mode) -> bool {
+        A::supports(mode) || B::supports(mode)
+    }
+
+    fn run(
+        measurement: &Measurement,
+        mode: cmd::Mode,
+        task: &Task<P>,
+    ) -> Result<Verdict<P>, ModeError> {
+        if A::supports(mode) {
+            A::run(measurement, mode, task)
+        } else {
+            B::run(measurement, mode, task)
+        }
+    }
+}
+
+/// Strassen modes, which subtract the products of quadrants.
+struct StrassenModes;
+impl<P: Product<Value: Ring>> Extension<P> for StrassenModes {
+    fn supports(mode: cmd::Mode) -> bool {
+        matches!(mode, cmd::Mode::CpuStrassen | cmd::Mode::GpuStrassen)
+    }
+
+    fn run(
+        measurement: &Measurement,
+        mode: cmd::Mode,
+        task: &Task<P>,
+    ) -> Result<Verdict<P>, ModeError> {
+        let (window, only, cutoff) = (
+            measurement.window,
+            measurement.only.as_deref(),
+            measurement.cutoff,
+        );
+        match mode {
+            cmd::Mode::CpuStrassen => Ok(measurement.pool.install(|| {
+                let strassen = Strassen {
+                    inner: Blocked,
+                    cutoff,
+                };
+                Measurement::run_cpu(strassen, false, task, window, only)
+            })),
+            cmd::Mode::GpuStrassen => measurement.run_gpu(mode, task, |executor| {
+                let mut strassen = Strassen {
+                    inner: executor,
+                    cutoff,
+                };
+                match only {
+                    Some(indices) => Measurement::try_measure(|| {
+                        solver::solve_only(&mut strassen, task, window, indices)
+                    }),
+                    None => Measurement::try_measure(|| solver::solve(&mut strassen, task, window)),
+                }
+            }),
+            mode => unreachable!("{mode:?} is not a Strassen mode"),
+        }
+    }
+}
+
+/// Similarity modes, which invert the matrices on the host.
+struct SimilarityModes;
+impl<P: Product + Invert> Extension<P> for SimilarityModes {
+    fn supports(mode: cmd::Mode) -> bool {
+        matches!(mode, cmd::Mode::CpuSimilarity | cmd::Mode::GpuSimilarity)
+    }
+
+    fn run(
+        measurement: &Measurement,
+        mode: cmd::Mode,
+        task: &Task<P>,
+    ) -> Result<Verdict<P>, ModeError> {
+        let (window, only, max_condition) = (
+            measurement.window,
+            measurement.only.as_deref(),
+            measurement.max_condition,
+        );
+        match mode {
+            cmd::Mode::CpuSimilarity => {
+                let Ok(verdict) = measurement.pool.install(|| {
+                    Measurement::try_measure_similarity(|| match only {
+                        Some(indices) => {
+                            similarity::solve_only(&mut Cpu, task, window, max_condition, indices)
+                        }
+                        None => similarity::solve(&mut Cpu, task, window, max_condition),
+                    })
+                });
+                Ok(verdict)
+            }
+            cmd::Mode::GpuSimilarity => measurement.run_gpu(mode, task, |mut executor| {
+                Measurement::try_measure_similarity(|| match only {
+                    Some(indices) => {
+                        executor.solve_similarity_only(task, window, max_condition, indices)
+                    }
+                    None => executor.solve_similarity(task, window, max_condition),
+                })
+            }),
+            mode => unreachable!("{mode:?} is not a similarity mode"),
+        }
+    }
+}
+
+/// BLAS modes, which call the routine of the values.
+#[cfg(any(feature = "blas", feature = "openblas"))]
+struct BlasModes;
+#[cfg(any(feature = "blas", feature = "openblas"))]
+impl<P: Product<Value: blas::Gemm>> Extension<P> for BlasModes {
+    fn supports(mode: cmd::Mode) -> bool {
+        matches!(mode, cmd::Mode::CpuBlas | cmd::Mode::CpuBlasMultiThreaded)
+    }
+
+    fn run(
+        measurement: &Measurement,
+        mode: cmd::Mode,
+        task: &Task<P>,
+    ) -> Result<Verdict<P>, ModeError> {
+        let (window, only) = (measurement.window, measurement.only.as_deref());
+        let parallel = match mode {
+            cmd::Mode::CpuBlas => false,
+            cmd::Mode::CpuBlasMultiThreaded => true,
+            mode => unreachable!("{mode:?} is not a BLAS mode"),
+        };
+        Ok(measurement
+            .pool
+            .install(|| Measurement::run_cpu(blas::Blas, parallel, task, window, only)))
+    }
+}
+
+/// Representation of the matrices which the modes multiply.
+trait Product: Representation + Clone + Shape + Send + Sync {
+    /// The entry at (`row`, `col`) as a value and the binary exponent it is scaled by.
+    fn entry(&self, index: (usize, usize)) -> (Self::Value, i32);
+
+    fn entries(&self) -> impl Iterator<Item = (Self::Value, i32)> + '_ {
+        (0..self.cols())
+            .flat_map(move |col| (0..self.rows()).map(move |row| self.entry((row, col))))
+    }
+}
+
+impl<T: Semiring> Product for Matrix<T> {
+    fn entry(&self, index: (usize, usize)) -> (T, i32) {
+        (self[index], 0)
+    }
+}
+
+impl<T: Float> Product for Scaled<T> {
+    fn entry(&self, index: (usize, usize)) -> (T, i32) {
+        (self.matrix()[index], self.exponent())
+    }
+}
+
+/// Formats `value · 2^exponent`, which may be far beyond the range of `f64`.
+fn format_entry<T: Scalar>((value, exponent): (T, i32)) -> String {
+    let float = value.to_f64();
+    if exponent == 0 || float == 0. || !float.is_finite() {
+        return format!("{value:.6}");
+    }
+
+    let log = float.abs().log10() + f64::from(exponent) * LOG10_2;
+    let power = log.floor();
+    format!("{:.6}e{power}", 10f64.powf(log - power).copysign(float))
+}
+
+/// The number of NaN and infinite entries which are not expected in the semiring.
+fn non_finite<P: Product>(solution: &Solution<P>) -> usize {
+    solution
+        .0
+        .iter()
+        .flat_map(P::entries)
+        .filter(|(value, _)| !value.is_finite())
+        .count()
+}
+
+/// Describes how much the solution differs from the reference one.
+fn deviation<P: Product>(solution: &Solution<P>, reference: &Solution<P>) -> String {
+    let values = || {
+        iter::zip(&solution.0, &reference.0)
+            .flat_map(|(matrix, reference)| iter::zip(matrix.entries(), reference.entries()))
+    };
+
+    if P::Value::EXACT {
+        match values()
+            .filter(|(value, reference)| value != reference)
+            .count()
+        {
+            0 => "exact".to_owned(),
+            mismatches => format!("{mismatches} mismatches"),
+        }
+    } else {
+        let error = values()
+            .map(|(value, reference)| {
+                if value == reference {
+                    // Also covers infinities of tropical semirings.
+                    0.
+                } else {
+                    let ((value, exponent), (reference, reference_exponent)) = (value, reference);
+                    let value = value.to_f64() * 2f64.powi(exponent - reference_exponent);
+                    let reference = reference.to_f64();
+                    (value - reference).abs() / reference.abs().max(f64::MIN_POSITIVE)
+                }
+            })
+            // Unlike `f64::max`, this keeps NaN.
+            .fold(0., |max, error| {
+                if error > max || error.is_nan() {
+                    error
+                } else {
+                    max
+                }
+            });
+        format!("{error:.2e}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assigns_cores() {
+        let available = [0, 1, 2, 3];
+
+        assert_eq!(
+            assign_cores(5, &[2, 0], &available),
+            Ok(vec![2, 0, 2, 0, 2])
+        );
+        assert_eq!(assign_cores(6, &[], &available), Ok(vec![0, 1, 2, 3, 0, 1]));
+        assert_eq!(assign_cores(2, &[3, 1, 0], &available), Ok(vec![3, 1]));
+        assert_eq!(
+            assign_cores(2, &[1, 4], &available),
+            Err("core 4 is not one of the available cores [0, 1, 2, 3]".to_owned())
+        );
+        assert!(assign_cores(2, &[], &[]).is_err());
+    }
 }
@@ -1,23 +1,32 @@
 pub mod config;
 
+use std::borrow::Cow;
 use std::iter;
-use std::{num::NonZeroUsize, ptr};
+use std::ptr;
 
 use self::config::Config;
 use self::config::WorkSize;
 use opencl3::{
     command_queue::CommandQueue,
     context::Context,
-    error_codes::ClError,
+    device::Device,
+    error_codes::{
+        ClError, CL_CONTEXT_TERMINATED_KHR, CL_DEVICE_NOT_AVAILABLE,
+        CL_EXEC_STATUS_ERROR_FOR_EVENTS_IN_WAIT_LIST, CL_MEM_OBJECT_ALLOCATION_FAILURE,
+        CL_OUT_OF_HOST_MEMORY, CL_OUT_OF_RESOURCES,
+    },
     kernel::{ExecuteKernel, Kernel},
     memory::{Buffer, CL_MEM_READ_ONLY, CL_MEM_READ_WRITE},
     program::Program,
-    types::{cl_command_queue_properties, cl_float, cl_int, CL_BLOCKING},
+    types::{cl_command_queue_properties, cl_int, CL_BLOCKING},
 };
 
 use crate::{
-    task::{Matrix, Solution, Task},
-    types::ZERO,
+    semiring::Semiring,
+    similarity::{self, Conditioning, Invert},
+    solver::{self, Multiplier, Representation},
+    task::{Layout, Matrix, Shape, Solution, Task, Window},
+    types::Value,
 };
 
 #[cfg(feature = "profiling")]
@@ -31,7 +40,10 @@ const COMPILER_OPTIONS: &str = "";
 #[cfg(not(feature = "no-compiler-options"))]
 const COMPILER_OPTIONS: &str = opencl3::program::CL_STD_3_0;
 
+/// Common definitions prepended to each kernel's source.
+const PRELUDE: &str = include_str!("par/prelude.cl");
 const KERNEL_NAME: &str = "multiply";
+const TRANSPOSE_KERNEL_NAME: &str = "transpose";
 
 #[derive(thiserror::Error, Debug)]
 pub enum NewExecutorError {
@@ -39,55 +51,128 @@ pub enum NewExecutorError {
     TooBig(usize),
     #[error("dimension {0} cannot be converted to OpenCL int")]
     InconvertibleN(usize),
-    #[error("dimension should be multiple of {0} but is {1}")]
-    UnsupportedSize(NonZeroUsize, usize),
     #[error("OpenCL failed: {0}")]
     ClError(#[from] ClError),
     #[error("failed to compile OpenCL program: {0}")]
     Compile(String),
+    #[error("work-group configuration is not supported by the device")]
+    UnsupportedConfig,
+    #[error("device does not support {0} extension")]
+    MissingExtension(&'static str),
 }
 
-pub struct Executor {
+#[derive(thiserror::Error, Debug)]
+pub enum ExecuteError {
+    #[error("matrices of dimensions {}x{} and {}x{} cannot be multiplied", .a.0, .a.1, .b.0, .b.1)]
+    DimensionMismatch {
+        a: (usize, usize),
+        b: (usize, usize),
+    },
+    #[error("dimension {dimension} exceeds the executor's one {max}")]
+    TooBig { dimension: usize, max: usize },
+    #[error("device ran out of resources trying to {0}")]
+    OutOfResources(&'static str),
+    #[error("device was lost trying to {0}")]
+    DeviceLost(&'static str),
+    #[error("failed to {operation}: {cause}")]
+    Enqueue {
+        operation: &'static str,
+        cause: ClError,
+    },
+}
+
+impl ExecuteError {
+    /// Classifies the error of an OpenCL call performing the `operation`.
+    fn enqueue(operation: &'static str) -> impl FnOnce(ClError) -> Self {
+        move |cause| match cause.0 {
+            CL_OUT_OF_RESOURCES | CL_OUT_OF_HOST_MEMORY | CL_MEM_OBJECT_ALLOCATION_FAILURE => {
+                Self::OutOfResources(operation)
+            }
+            CL_DEVICE_NOT_AVAILABLE
+            | CL_CONTEXT_TERMINATED_KHR
+            | CL_EXEC_STATUS_ERROR_FOR_EVENTS_IN_WAIT_LIST => Self::DeviceLost(operation),
+            _ => Self::Enqueue { operation, cause },
+        }
+    }
+}
+
+pub struct Executor<T: Semiring = Value> {
     work_size: WorkSize,
     // context: Context,
     command_queue: CommandQueue,
     // program: Program,
     kernel: Kernel,
+    /// The `transpose` kernel and the buffer for `B^T` if the configuration needs them.
+    transpose: Option<(Kernel, Buffer<T>)>,
+    /// The largest dimension of the multiplied matrices.
     n: usize,
-    n_int: cl_int,
-    buffer_size: usize,
-    a_buffer: Buffer<cl_float>,
-    b_buffer: Buffer<cl_float>,
-    c_buffer: Buffer<cl_float>,
+    /// Device-side dimension of the largest matrices, i.e. `n` rounded up to the tile.
+    padded_n: usize,
+    /// Each dimension of the matrices is rounded up to a multiple of this on the device.
+    tile: usize,
+    a_buffer: Buffer<T>,
+    b_buffer: Buffer<T>,
+    c_buffer: Buffer<T>,
 }
-impl Executor {
+impl<T: Semiring> Executor<T> {
+    /// Creates an executor for matrices whose dimensions do not exceed `n`.
     pub fn new(n: usize, context: &Context, config: Config) -> Result<Self, NewExecutorError> {
-        let buffer_size = n.checked_mul(n).ok_or(NewExecutorError::TooBig(n))?;
-        let n_int = cl_int::try_from(n).map_err(|_| NewExecutorError::InconvertibleN(n))?;
-
-        let local_size = config
-            .work_size
-            .local
-            .unwrap_or(const { NonZeroUsize::new(1).unwrap() });
-        if n % local_size != 0 {
-            return Err(NewExecutorError::UnsupportedSize(local_size, n));
+        let tile = config.work_size.tile();
+        // Matrices are transparently zero-padded so that the kernels only see whole tiles.
+        let padded_n = n
+            .checked_next_multiple_of(tile)
+            .ok_or(NewExecutorError::TooBig(n))?;
+        let buffer_size = padded_n
+            .checked_mul(padded_n)
+            .ok_or(NewExecutorError::TooBig(n))?;
+        if cl_int::try_from(padded_n).is_err() {
+            return Err(NewExecutorError::InconvertibleN(padded_n));
+        }
+
+        for device in context.devices() {
+            let device = Device::new(*device);
+            if let Some(extension) = T::CL_EXTENSION {
+                if !config::has_extension(&device, extension) {
+                    return Err(NewExecutorError::MissingExtension(extension));
+                }
+            }
+            if !config.supports::<T>(&device) {
+                return Err(NewExecutorError::UnsupportedConfig);
+            }
         }
 
         let command_queue =
             CommandQueue::create_default_with_properties(context, COMMAND_QUEUE_FLAGS, 0)?;
-        let program = Program::create_and_build_from_source(context, config.src, COMPILER_OPTIONS)
-            .map_err(NewExecutorError::Compile)?;
+        let program = Program::create_and_build_from_sources(
+            context,
+            &[&T::cl_definitions(), PRELUDE, config.src],
+            &format!(
+                "{COMPILER_OPTIONS} -DVALUE={} {}",
+                T::CL_TYPE,
+                config.options
+            ),
+        )
+        .map_err(NewExecutorError::Compile)?;
         let kernel = Kernel::create(&program, KERNEL_NAME)?;
 
-        let a_buffer: Buffer<f32> = unsafe {
-            Buffer::<cl_float>::create(context, CL_MEM_READ_ONLY, buffer_size, ptr::null_mut())
+        let a_buffer = unsafe {
+            Buffer::<T>::create(context, CL_MEM_READ_ONLY, buffer_size, ptr::null_mut())
         }?;
         let b_buffer = unsafe {
-            Buffer::<cl_float>::create(context, CL_MEM_READ_ONLY, buffer_size, ptr::null_mut())
+            Buffer::<T>::create(context, CL_MEM_READ_ONLY, buffer_size, ptr::null_mut())
         }?;
         let c_buffer = unsafe {
-            Buffer::<cl_float>::create(context, CL_MEM_READ_WRITE, buffer_size, ptr::null_mut())
+            Buffer::<T>::create(context, CL_MEM_READ_WRITE, buffer_size, ptr::null_mut())
         }?;
+        let transpose = if config.transpose_b {
+            let kernel = Kernel::create(&program, TRANSPOSE_KERNEL_NAME)?;
+            let buffer = unsafe {
+                Buffer::<T>::create(context, CL_MEM_READ_WRITE, buffer_size, ptr::null_mut())
+            }?;
+            Some((kernel, buffer))
+        } else {
+            None
+        };
 
         Ok(Self {
             work_size: config.work_size,
@@ -95,145 +180,287 @@ impl Executor {
             command_queue,
             // program,
             kernel,
+            transpose,
             n,
-            n_int,
-            buffer_size,
+            padded_n,
+            tile,
             a_buffer,
             b_buffer,
             c_buffer,
         })
     }
 
-    pub fn solve(&mut self, task: &Task) -> Solution {
-        assert!(
-            task.n() == self.n,
-            "Task dimension shoud match this solver's one"
-        );
-        let n = task.matrices().len();
-
-        Solution(
-            (0..n)
-                .map(|index| {
-                    unsafe {
-                        self.multiply_all_unchecked(
-                            task.matrices().iter().cycle().skip(index).take(n).cloned(),
-                        )
-                    }
-                    .expect("This is unrechable when `n` is zero")
-                })
-                .collect(),
-        )
+    /// The number of zero rows and columns appended to the largest matrix on the device.
+    pub const fn padding(&self) -> usize {
+        self.padded_n - self.n
     }
 
-    pub fn solve_memoizing(&mut self, task: &Task) -> Solution {
-        assert!(
-            task.n() == self.n,
-            "Task dimension shoud match this solver's one"
-        );
-        let n = task.matrices().len();
-        let Some(first) = task.matrices().first() else {
-            return Solution(vec![]);
-        };
-        if n == 1 {
-            return Solution(vec![first.clone()]);
-        }
+    pub fn solve<M: Clone + Shape>(
+        &mut self,
+        task: &Task<M>,
+        window: Window,
+    ) -> Result<Solution<M>, ExecuteError>
+    where
+        Self: Multiplier<M, Error = ExecuteError>,
+    {
+        self.stream(task, window)?
+            .collect::<Result<_, _>>()
+            .map(Solution)
+    }
 
-        let mut left_muls = Vec::with_capacity(n);
-        left_muls.push(first.clone());
-        for index in 0..(n - 1) {
-            left_muls.push(unsafe {
-                self.multiply_unchecked(&left_muls[index], &task.matrices()[index + 1])
-            });
-        }
+    /// Like [`Self::solve`], but yields each window as soon as it is read from the device,
+    /// ending with the first failure.
+    pub fn stream<'a, M: Clone + Shape>(
+        &'a mut self,
+        task: &'a Task<M>,
+        window: Window,
+    ) -> Result<impl Iterator<Item = Result<M, ExecuteError>> + 'a, ExecuteError>
+    where
+        Self: Multiplier<M, Error = ExecuteError>,
+    {
+        self.check_fits(task)?;
+        Ok(solver::stream(self, task, window))
+    }
 
-        let last = task.matrices().last().unwrap();
-        let mut right_muls = Vec::with_capacity(n - 1);
-        right_muls.push(last.clone());
-        for index in 0..n - 2 {
-            right_muls.push(unsafe {
-                self.multiply_unchecked(&task.matrices()[n - index - 2], &right_muls[index])
-            })
-        }
+    pub fn solve_memoizing<M: Clone + Shape>(
+        &mut self,
+        task: &Task<M>,
+        window: Window,
+    ) -> Result<Solution<M>, ExecuteError>
+    where
+        Self: Multiplier<M, Error = ExecuteError>,
+    {
+        self.stream_memoizing(task, window)?
+            .collect::<Result<_, _>>()
+            .map(Solution)
+    }
 
-        Solution(
-            // Start with `left_muls[n-1]` which is actually `A[0] * ... * A[n-1]`,
-            //  then produce multiplications `right_muls[n-1 - (1..n)] * left_muls[1..n]`.
-            iter::zip(
-                left_muls.into_iter().cycle().skip(n - 1).take(n),
-                [None].into_iter().chain(right_muls.iter().rev().map(Some)),
-            )
-            .map(|(left, right)| -> Matrix {
-                if let Some(right) = right {
-                    unsafe { self.multiply_unchecked(right, &left) }
-                } else {
-                    left
-                }
-            })
-            .collect(),
-        )
+    /// Like [`Self::solve_memoizing`], but yields each window as soon as it is read from the device,
+    /// ending with the first failure.
+    pub fn stream_memoizing<'a, M: Clone + Shape>(
+        &'a mut self,
+        task: &'a Task<M>,
+        window: Window,
+    ) -> Result<impl Iterator<Item = Result<M, ExecuteError>> + 'a, ExecuteError>
+    where
+        Self: Multiplier<M, Error = ExecuteError>,
+    {
+        self.check_fits(task)?;
+        Ok(solver::stream_memoizing(self, task, window))
     }
 
-    pub unsafe fn multiply_all_unchecked(
+    pub fn solve_only<M: Clone + Shape>(
         &mut self,
-        matrices: impl Iterator<Item = Matrix>,
-    ) -> Option<Matrix> {
-        matrices.reduce(|l, r| unsafe { self.multiply_unchecked(&l, &r) })
+        task: &Task<M>,
+        window: Window,
+        indices: &[usize],
+    ) -> Result<Solution<M>, ExecuteError>
+    where
+        Self: Multiplier<M, Error = ExecuteError>,
+    {
+        self.stream_only(task, window, indices)?
+            .collect::<Result<_, _>>()
+            .map(Solution)
     }
 
-    #[cfg(test)]
-    fn multiply(&mut self, a: &Matrix, b: &Matrix) -> Matrix {
-        assert!(a.n() == b.n(), "Matrices should have the same dimensions");
-        assert!(
-            a.n() == self.n,
-            "Matrices should be of dimmension {} but are of dimension {}",
-            self.n,
-            a.n()
-        );
+    /// Like [`Self::solve_only`], but yields each window as soon as it is read from the device,
+    /// ending with the first failure.
+    pub fn stream_only<'a, M: Clone + Shape>(
+        &'a mut self,
+        task: &'a Task<M>,
+        window: Window,
+        indices: &'a [usize],
+    ) -> Result<impl Iterator<Item = Result<M, ExecuteError>> + 'a, ExecuteError>
+    where
+        Self: Multiplier<M, Error = ExecuteError>,
+    {
+        self.check_fits(task)?;
+        Ok(solver::stream_only(self, task, window, indices))
+    }
 
-        unsafe { self.multiply_unchecked(a, b) }
+    /// Transforms the windows by similarity, inverting the matrices on the host.
+    pub fn solve_similarity<M: Clone + Shape + Invert>(
+        &mut self,
+        task: &Task<M>,
+        window: Window,
+        max_condition: f64,
+    ) -> Result<(Solution<M>, Conditioning), ExecuteError>
+    where
+        Self: Multiplier<M, Error = ExecuteError>,
+    {
+        let (windows, conditioning) = self.stream_similarity(task, window, max_condition)?;
+        Ok((Solution(windows.collect::<Result<_, _>>()?), conditioning))
     }
-    pub unsafe fn multiply_unchecked(&mut self, a: &Matrix, b: &Matrix) -> Matrix {
-        let _ = unsafe {
-            self.command_queue.enqueue_write_buffer(
-                &mut self.a_buffer,
-                CL_BLOCKING,
-                0,
-                a.as_slice(),
-                &[],
-            )
+
+    /// Like [`Self::solve_similarity`], but yields each window as soon as it is read from the device,
+    /// ending with the first failure.
+    pub fn stream_similarity<'a, M: Clone + Shape + Invert>(
+        &'a mut self,
+        task: &'a Task<M>,
+        window: Window,
+        max_condition: f64,
+    ) -> Result<
+        (
+            impl Iterator<Item = Result<M, ExecuteError>> + 'a,
+            Conditioning,
+        ),
+        ExecuteError,
+    >
+    where
+        Self: Multiplier<M, Error = ExecuteError>,
+    {
+        self.check_fits(task)?;
+        Ok(similarity::stream(self, task, window, max_condition))
+    }
+
+    pub fn solve_similarity_only<M: Clone + Shape + Invert>(
+        &mut self,
+        task: &Task<M>,
+        window: Window,
+        max_condition: f64,
+        indices: &[usize],
+    ) -> Result<(Solution<M>, Conditioning), ExecuteError>
+    where
+        Self: Multiplier<M, Error = ExecuteError>,
+    {
+        let (windows, conditioning) =
+            self.stream_similarity_only(task, window, max_condition, indices)?;
+        Ok((Solution(windows.collect::<Result<_, _>>()?), conditioning))
+    }
+
+    /// Like [`Self::solve_similarity_only`],
+    /// but yields each window as soon as it is read from the device,
+    /// ending with the first failure.
+    pub fn stream_similarity_only<'a, M: Clone + Shape + Invert>(
+        &'a mut self,
+        task: &'a Task<M>,
+        window: Window,
+        max_condition: f64,
+        indices: &'a [usize],
+    ) -> Result<
+        (
+            impl Iterator<Item = Result<M, ExecuteError>> + 'a,
+            Conditioning,
+        ),
+        ExecuteError,
+    >
+    where
+        Self: Multiplier<M, Error = ExecuteError>,
+    {
+        self.check_fits(task)?;
+        Ok(similarity::stream_only(
+            self,
+            task,
+            window,
+            max_condition,
+            indices,
+        ))
+    }
+
+    fn check_fits(&self, task: &Task<impl Shape>) -> Result<(), ExecuteError> {
+        match task.max_dimension() {
+            dimension if dimension > self.n => Err(ExecuteError::TooBig {
+                dimension,
+                max: self.n,
+            }),
+            _ => Ok(()),
         }
-        .expect("Failed to write A");
-        _ = unsafe {
-            self.command_queue.enqueue_write_buffer(
-                &mut self.b_buffer,
-                CL_BLOCKING,
-                0,
-                b.as_slice(),
-                &[],
-            )
+    }
+
+    /// Multiplies the matrices on the device, checking that their dimensions match
+    /// and do not exceed the one of this executor.
+    pub fn try_multiply(
+        &mut self,
+        a: &Matrix<T>,
+        b: &Matrix<T>,
+    ) -> Result<Matrix<T>, ExecuteError> {
+        let [m, k, n] = [a.rows(), a.cols(), b.cols()];
+        if k != b.rows() {
+            return Err(ExecuteError::DimensionMismatch {
+                a: (m, k),
+                b: (b.rows(), n),
+            });
+        }
+        let dimension = m.max(k).max(n);
+        if dimension > self.n {
+            return Err(ExecuteError::TooBig {
+                dimension,
+                max: self.n,
+            });
         }
-        .expect("Failed to write B");
+
+        // A pair of row-major matrices is a pair of column-major transposes, so that
+        // the row-major `A · B` is the column-major `Bᵀ · Aᵀ` and neither is copied.
+        if (a.layout(), b.layout()) == (Layout::RowMajor, Layout::RowMajor) {
+            let c = self.execute([n, k, m], b.as_slice(), a.as_slice())?;
+            return Ok(Matrix::from_row_major(m, n, c).expect("Dimensions should match"));
+        }
+        let (a, b) = (
+            a.to_layout(Layout::ColumnMajor),
+            b.to_layout(Layout::ColumnMajor),
+        );
+        let c = self.execute([m, k, n], a.as_slice(), b.as_slice())?;
+        Ok(Matrix::new(m, n, c).expect("Dimensions should match"))
+    }
+
+    /// Multiplies column-major `m × k` and `k × n` matrices which fit into the buffers.
+    fn execute(&mut self, [m, k, n]: [usize; 3], a: &[T], b: &[T]) -> Result<Vec<T>, ExecuteError> {
+        // These do not exceed `padded_n` which is convertible to `cl_int`.
+        let [padded_m, padded_k, padded_n] = [m, k, n].map(|d| d.next_multiple_of(self.tile));
+        let [m_int, k_int, n_int] = [padded_m, padded_k, padded_n].map(|d| d as cl_int);
+
+        // The padded matrices fit into the buffers, since the dimensions do not exceed `n`.
+        let a = pad(a, [m, k], [padded_m, padded_k]);
+        unsafe {
+            self.command_queue
+                .enqueue_write_buffer(&mut self.a_buffer, CL_BLOCKING, 0, &a, &[])
+        }
+        .map_err(ExecuteError::enqueue("write A"))?;
+        let b = pad(b, [k, n], [padded_k, padded_n]);
+        unsafe {
+            self.command_queue
+                .enqueue_write_buffer(&mut self.b_buffer, CL_BLOCKING, 0, &b, &[])
+        }
+        .map_err(ExecuteError::enqueue("write B"))?;
+
+        let b_buffer = if let Some((transpose_kernel, bt_buffer)) = &self.transpose {
+            // The queue is in-order, so the multiplication will wait for the transposition.
+            unsafe {
+                ExecuteKernel::new(transpose_kernel)
+                    .set_arg(&k_int)
+                    .set_arg(&n_int)
+                    .set_arg(&self.b_buffer)
+                    .set_arg(bt_buffer)
+                    .set_global_work_sizes(&[padded_k, padded_n])
+                    .enqueue_nd_range(&self.command_queue)
+            }
+            .map_err(ExecuteError::enqueue("transpose B"))?;
+            bt_buffer
+        } else {
+            &self.b_buffer
+        };
 
         let mut execute_kernel = ExecuteKernel::new(&self.kernel);
         let kernel_event = unsafe {
             execute_kernel
-                .set_arg(&self.n_int)
+                .set_arg(&m_int)
+                .set_arg(&n_int)
+                .set_arg(&k_int)
                 .set_arg(&self.a_buffer)
-                .set_arg(&self.b_buffer)
+                .set_arg(b_buffer)
                 .set_arg(&self.c_buffer)
-                .set_global_work_sizes(&[self.n, self.n / self.work_size.per_thread]);
-            if let Some(local) = self.work_size.local {
-                execute_kernel
-                    .set_local_work_sizes(&[local.get(), local.get() / self.work_size.per_thread]);
+                .set_global_work_sizes(&self.work_size.global_sizes(padded_m, padded_n));
+            if let Some(local) = self.work_size.local_sizes() {
+                execute_kernel.set_local_work_sizes(&local);
             }
 
             execute_kernel.enqueue_nd_range(&self.command_queue)
         }
-        .expect("Failed to create kernel event");
+        .map_err(ExecuteError::enqueue("run the kernel"))?;
 
         let events = vec![kernel_event.get()];
-        let mut result = vec![ZERO; self.buffer_size];
-        _ = unsafe {
+        let mut result = vec![T::ZERO; padded_m * padded_n];
+        unsafe {
             self.command_queue.enqueue_read_buffer(
                 &self.c_buffer,
                 CL_BLOCKING,
@@ -242,28 +469,70 @@ impl Executor {
                 &events,
             )
         }
-        .expect("Failed to wait for read event");
+        .map_err(ExecuteError::enqueue("read C"))?;
 
         #[cfg(feature = "profiling")]
         {
             // Note: this will take time in execution time measurement.
             let start_time = kernel_event
                 .profiling_command_start()
-                .expect("Failed to get start time");
+                .map_err(ExecuteError::enqueue("get the start time"))?;
             let end_time = kernel_event
                 .profiling_command_end()
-                .expect("Failed to get end time");
+                .map_err(ExecuteError::enqueue("get the end time"))?;
             tracing::debug!(
                 "GPU task took {:?}",
                 std::time::Duration::from_nanos(end_time - start_time)
             );
         }
 
-        result
-            .into_boxed_slice()
-            .try_into()
-            .expect("Dimensions should match")
+        Ok(crop(result, padded_m, m, n))
+    }
+}
+
+impl<T: Semiring, M: Representation<Value = T>> Multiplier<M> for Executor<T> {
+    type Error = ExecuteError;
+
+    fn multiply(&mut self, a: &M, b: &M) -> Result<M, ExecuteError> {
+        M::product(a, b, |a, b| self.try_multiply(a, b))
+    }
+}
+
+/// Lays the column-major `rows × cols` values out in a zero-padded `padded_rows × padded_cols`
+/// buffer.
+fn pad<T: Semiring>(
+    values: &[T],
+    [rows, cols]: [usize; 2],
+    [padded_rows, padded_cols]: [usize; 2],
+) -> Cow<'_, [T]> {
+    if rows == padded_rows && cols == padded_cols {
+        return Cow::Borrowed(values);
+    }
+
+    let mut padded = vec![T::ZERO; padded_rows * padded_cols];
+    if rows > 0 {
+        for (padded, column) in iter::zip(
+            padded.chunks_exact_mut(padded_rows),
+            values.chunks_exact(rows),
+        ) {
+            padded[..rows].copy_from_slice(column);
+        }
     }
+    Cow::Owned(padded)
+}
+
+/// Strips the padding from a buffer with `padded_rows` rows.
+fn crop<T: Copy>(values: Vec<T>, padded_rows: usize, rows: usize, cols: usize) -> Vec<T> {
+    if padded_rows == rows && values.len() == rows * cols {
+        return values;
+    }
+
+    values
+        .chunks_exact(padded_rows)
+        .take(cols)
+        .flat_map(|column| &column[..rows])
+        .copied()
+        .collect()
 }
 
 #[cfg(test)]
@@ -291,8 +560,121 @@ mod tests {
         let b = Matrix::from_vec(vec![5., 7., 6., 8.]).unwrap();
 
         assert_eq!(
-            executor.multiply(&a, &b),
+            executor.multiply(&a, &b).unwrap(),
             Matrix::from_vec(vec![19., 43., 22., 50.]).unwrap(),
         );
     }
+
+    #[test]
+    fn rejects_invalid_dimensions() {
+        let device = device();
+        let context = Context::from_device(&device).unwrap();
+        let mut executor = Executor::new(3, &context, config::V1).unwrap();
+
+        let a = Matrix::new(2, 3, vec![1.; 6]).unwrap();
+        let b = Matrix::new(2, 2, vec![1.; 4]).unwrap();
+        assert!(matches!(
+            executor.try_multiply(&a, &b),
+            Err(ExecuteError::DimensionMismatch {
+                a: (2, 3),
+                b: (2, 2)
+            })
+        ));
+
+        let big = Matrix::new(4, 4, vec![1.; 16]).unwrap();
+        let task = Task::from_vec(vec![big.clone(), big]).unwrap();
+        assert!(matches!(
+            executor.solve(&task, Window::full(&task)),
+            Err(ExecuteError::TooBig {
+                dimension: 4,
+                max: 3
+            })
+        ));
+        assert_eq!(
+            executor.try_multiply(&b, &b).unwrap(),
+            crate::seq::multiply(&b, &b)
+        );
+    }
+
+    #[test]
+    fn padded_3x3() {
+        let device = device();
+        let context = Context::from_device(&device).unwrap();
+        let mut executor = Executor::new(3, &context, config::V2).unwrap();
+        assert_eq!(executor.padding(), 5);
+
+        let a = Matrix::from_vec(vec![1., 4., 7., 2., 5., 8., 3., 6., 9.]).unwrap();
+        let b = Matrix::from_vec(vec![9., 6., 3., 8., 5., 2., 7., 4., 1.]).unwrap();
+
+        assert_eq!(
+            executor.multiply(&a, &b).unwrap(),
+            crate::seq::multiply(&a, &b)
+        );
+    }
+
+    #[test]
+    fn rectangular() {
+        let device = device();
+        let context = Context::from_device(&device).unwrap();
+        let mut executor = Executor::new(5, &context, config::V3).unwrap();
+
+        let a = Matrix::new(3, 5, (0..15).map(|i| i as f32).collect()).unwrap();
+        let b = Matrix::new(5, 2, (0..10).map(|i| (i % 3) as f32).collect()).unwrap();
+
+        let c = executor.multiply(&a, &b).unwrap();
+        assert_eq!((c.rows(), c.cols()), (3, 2));
+        assert_eq!(c, crate::seq::multiply(&a, &b));
+
+        let (a, b) = (a.to_layout(Layout::RowMajor), b.to_layout(Layout::RowMajor));
+        let c = executor.try_multiply(&a, &b).unwrap();
+        assert_eq!((c.layout(), c.rows(), c.cols()), (Layout::RowMajor, 3, 2));
+        assert_eq!(c, crate::seq::multiply(&a, &b));
+    }
+
+    #[test]
+    fn all_configs_match_cpu() {
+        let device = device();
+        let context = Context::from_device(&device).unwrap();
+
+        let n = 37;
+        let a = Matrix::from_vec((0..n * n).map(|i| (i % 7) as f32).collect()).unwrap();
+        let b = Matrix::from_vec((0..n * n).map(|i| (i % 5) as f32 - 2.).collect()).unwrap();
+        let expected = crate::seq::multiply(&a, &b);
+
+        for config in [
+            config::V1,
+            config::V2,
+            config::V3,
+            config::V4,
+            config::V5,
+            config::V6,
+            config::V7,
+            config::V8,
+        ] {
+            if !config.supports::<f32>(&device) {
+                continue;
+            }
+            let mut executor = Executor::new(n, &context, config).unwrap();
+            assert_eq!(executor.multiply(&a, &b).unwrap(), expected, "{config:?}");
+        }
+    }
+
+    #[test]
+    fn double_precision() {
+        let device = device();
+        let context = Context::from_device(&device).unwrap();
+        let mut executor = match Executor::<f64>::new(2, &context, config::V2) {
+            Ok(executor) => executor,
+            Err(NewExecutorError::MissingExtension(_)) => return,
+            Err(cause) => panic!("{cause}"),
+        };
+
+        let a = Matrix::from_vec(vec![0.125, 0.375, 0.25, 0.5]).unwrap();
+        let b = Matrix::from_vec(vec![0.625, 0.875, 0.75, 1.]).unwrap();
+
+        assert_eq!(
+            executor.multiply(&a, &b).unwrap(),
+            crate::seq::multiply(&a, &b)
+        );
+    }
 }
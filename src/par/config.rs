@@ -1,35 +1,216 @@
-use std::num::NonZeroUsize;
+use std::{mem, num::NonZeroUsize};
+
+use opencl3::device::Device;
+
+use crate::types::Scalar;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
     pub(super) src: &'static str,
+    /// Additional options passed to the OpenCL compiler.
+    pub(super) options: &'static str,
+    /// Whether `B` is transposed by the `transpose` kernel before being passed to `multiply`.
+    pub(super) transpose_b: bool,
     pub(super) work_size: WorkSize,
+    /// The number of values stored in local memory by a single work-group.
+    pub(super) local_values: usize,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct WorkSize {
     pub(super) local: Option<NonZeroUsize>,
-    pub(super) per_thread: NonZeroUsize,
+    /// The number of values computed by a single work-item in each of the dimensions.
+    pub(super) per_thread: [NonZeroUsize; 2],
+}
+
+impl Config {
+    /// Checks whether the device can run the kernel with this work-group configuration
+    /// on values of the given type.
+    pub fn supports<T: Scalar>(&self, device: &Device) -> bool {
+        if T::CL_EXTENSION.is_some_and(|extension| !has_extension(device, extension)) {
+            return false;
+        }
+        let Some(local) = self.work_size.local_sizes() else {
+            return true;
+        };
+
+        let fits_work_group = device
+            .max_work_group_size()
+            .is_ok_and(|max| local.iter().product::<usize>() <= max);
+        let fits_work_items = device.max_work_item_sizes().is_ok_and(|max| {
+            max.len() >= local.len() && local.iter().zip(&max).all(|(size, max)| size <= max)
+        });
+        let fits_local_memory = device
+            .local_mem_size()
+            .is_ok_and(|max| (self.local_values * mem::size_of::<T>()) as u64 <= max);
+
+        fits_work_group && fits_work_items && fits_local_memory
+    }
+}
+
+pub(super) fn has_extension(device: &Device, extension: &str) -> bool {
+    device
+        .extensions()
+        .is_ok_and(|extensions| extensions.split_whitespace().any(|name| name == extension))
 }
 
+impl WorkSize {
+    /// Global sizes computing a `rows × cols` matrix, both of which are multiples of the tile.
+    pub(super) fn global_sizes(&self, rows: usize, cols: usize) -> [usize; 2] {
+        [rows / self.per_thread[0], cols / self.per_thread[1]]
+    }
+
+    /// The size of the tile which dimensions of the multiplied matrices should be multiple of.
+    pub(super) fn tile(&self) -> usize {
+        self.local.map_or(1, NonZeroUsize::get)
+    }
+
+    pub(super) fn local_sizes(&self) -> Option<[usize; 2]> {
+        self.local.map(|local| {
+            [
+                local.get() / self.per_thread[0],
+                local.get() / self.per_thread[1],
+            ]
+        })
+    }
+}
+
+const ONE: NonZeroUsize = NonZeroUsize::new(1).unwrap();
+
 pub const V1: Config = Config {
     src: include_str!("multiply1.cl"),
+    options: "",
+    transpose_b: false,
     work_size: WorkSize {
         local: None,
-        per_thread: NonZeroUsize::new(1).unwrap(),
+        per_thread: [ONE, ONE],
     },
+    local_values: 0,
 };
 pub const V2: Config = Config {
     src: include_str!("multiply2.cl"),
+    options: "",
+    transpose_b: false,
     work_size: WorkSize {
         local: Some(NonZeroUsize::new(8).unwrap()),
-        per_thread: NonZeroUsize::new(1).unwrap(),
+        per_thread: [ONE, ONE],
     },
+    local_values: 2 * 8 * 8,
 };
 pub const V3: Config = Config {
     src: include_str!("multiply3.cl"),
+    options: "",
+    transpose_b: false,
+    work_size: WorkSize {
+        local: Some(NonZeroUsize::new(16).unwrap()),
+        per_thread: [ONE, NonZeroUsize::new(4).unwrap()],
+    },
+    local_values: 2 * 16 * 16,
+};
+/// 2D register blocking: each work-item computes a 4x4 block of `C`.
+pub const V4: Config = Config {
+    src: include_str!("multiply4.cl"),
+    options: "",
+    transpose_b: false,
+    work_size: WorkSize {
+        local: Some(NonZeroUsize::new(32).unwrap()),
+        per_thread: [NonZeroUsize::new(4).unwrap(), NonZeroUsize::new(4).unwrap()],
+    },
+    local_values: 2 * 32 * 32,
+};
+/// Global memory is accessed with 4-wide vector loads and stores.
+pub const V5: Config = Config {
+    src: include_str!("multiply5.cl"),
+    options: "-DWIDTH=4",
+    transpose_b: false,
+    work_size: WorkSize {
+        local: Some(NonZeroUsize::new(32).unwrap()),
+        per_thread: [NonZeroUsize::new(4).unwrap(), ONE],
+    },
+    local_values: 2 * 32 * 32,
+};
+/// Global memory is accessed with 8-wide vector loads and stores.
+pub const V6: Config = Config {
+    src: include_str!("multiply5.cl"),
+    options: "-DWIDTH=8",
+    transpose_b: false,
+    work_size: WorkSize {
+        local: Some(NonZeroUsize::new(32).unwrap()),
+        per_thread: [NonZeroUsize::new(8).unwrap(), ONE],
+    },
+    local_values: 2 * 32 * 32,
+};
+/// `B` is transposed on the device so that both tiles are loaded with coalesced accesses.
+pub const V7: Config = Config {
+    src: include_str!("multiply6.cl"),
+    options: "",
+    transpose_b: true,
     work_size: WorkSize {
         local: Some(NonZeroUsize::new(16).unwrap()),
-        per_thread: NonZeroUsize::new(4).unwrap(),
+        per_thread: [ONE, ONE],
     },
+    local_values: 2 * 16 * 16,
 };
+/// Tiles of `B` are transposed in local memory which is padded to avoid bank conflicts.
+pub const V8: Config = Config {
+    src: include_str!("multiply7.cl"),
+    options: "",
+    transpose_b: false,
+    work_size: WorkSize {
+        local: Some(NonZeroUsize::new(16).unwrap()),
+        per_thread: [ONE, ONE],
+    },
+    local_values: 16 * 16 + 16 * 17,
+};
+
+/// Configurations by the names they are serialized as, since their sources are not.
+#[cfg(feature = "serde")]
+const NAMED: [(&str, Config); 8] = [
+    ("V1", V1),
+    ("V2", V2),
+    ("V3", V3),
+    ("V4", V4),
+    ("V5", V5),
+    ("V6", V6),
+    ("V7", V7),
+    ("V8", V8),
+];
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Config {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (name, _) = NAMED
+            .iter()
+            .find(|(_, config)| (config.src, config.options) == (self.src, self.options))
+            .ok_or_else(|| serde::ser::Error::custom("the configuration has no name"))?;
+        serializer.serialize_str(name)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Config {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = <String as serde::Deserialize>::deserialize(deserializer)?;
+        NAMED
+            .iter()
+            .find(|&&(known, _)| known == name)
+            .map(|&(_, config)| config)
+            .ok_or_else(|| serde::de::Error::custom(format_args!("unknown configuration {name:?}")))
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde() {
+        for (name, config) in NAMED {
+            let json = serde_json::to_string(&config).unwrap();
+            assert_eq!(json, format!("{name:?}"));
+            let parsed: Config = serde_json::from_str(&json).unwrap();
+            assert_eq!((parsed.src, parsed.options), (config.src, config.options));
+        }
+        assert!(serde_json::from_str::<Config>(r#""V9""#).is_err());
+    }
+}
@@ -1,16 +1,18 @@
 kernel void multiply(
+    const int M,
     const int N,
-    const global float* A,
-    const global float* B,
-    global float* C
+    const int K,
+    const global VALUE* A,
+    const global VALUE* B,
+    global VALUE* C
 ) {
     const int globalRow = get_global_id(0);
     const int globalCol = get_global_id(1);
 
-    float value = 0.;
-    for (int k = 0; k < N; k++) {
-        value += A[k * N + globalRow] * B[globalCol * N + k];
+    VALUE value = ZERO;
+    for (int k = 0; k < K; k++) {
+        value = ADD(value, MUL(A[k * M + globalRow], B[globalCol * K + k]));
     }
 
-    C[globalCol * N + globalRow] = value;
+    C[globalCol * M + globalRow] = value;
 }
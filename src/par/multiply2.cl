@@ -1,8 +1,10 @@
 kernel void multiply(
+    const int M,
     const int N,
-    const global float* A,
-    const global float* B,
-    global float* C
+    const int K,
+    const global VALUE* A,
+    const global VALUE* B,
+    global VALUE* C
 ) {
     const int TS = 8;
 
@@ -11,26 +13,26 @@ kernel void multiply(
     const int globalRow = TS * get_group_id(0) + row;
     const int globalCol = TS * get_group_id(1) + col;
 
-    local float ASub[TS][TS];
-    local float BSub[TS][TS];
+    local VALUE ASub[TS][TS];
+    local VALUE BSub[TS][TS];
 
-    float value = 0.;
-    const int numTiles = N / TS;
+    VALUE value = ZERO;
+    const int numTiles = K / TS;
     for (int tile = 0; tile < numTiles; tile++) {
         const int tiledRow = TS * tile + row;
         const int tiledCol = TS * tile + col;
 
-        ASub[col][row] = A[tiledCol * N + globalRow];
-        BSub[col][row] = B[globalCol * N + tiledRow];
+        ASub[col][row] = A[tiledCol * M + globalRow];
+        BSub[col][row] = B[globalCol * K + tiledRow];
 
         barrier(CLK_LOCAL_MEM_FENCE);
 
         for (int k = 0; k < TS; k++) {
-            value += ASub[k][row] * BSub[col][k];
+            value = ADD(value, MUL(ASub[k][row], BSub[col][k]));
         }
 
         barrier(CLK_LOCAL_MEM_FENCE);
     }
 
-    C[globalCol * N + globalRow] = value;
+    C[globalCol * M + globalRow] = value;
 }
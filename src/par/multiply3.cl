@@ -1,8 +1,10 @@
 kernel void multiply(
+    const int M,
     const int N,
-    const global float* A,
-    const global float* B,
-    global float* C
+    const int K,
+    const global VALUE* A,
+    const global VALUE* B,
+    global VALUE* C
 ) {
     const int TS = 16;
     const int WPT = 4;
@@ -13,15 +15,15 @@ kernel void multiply(
     const int globalRow = TS * get_group_id(0) + row;
     const int globalCol = TS * get_group_id(1) + col;
 
-    local float ASub[TS][TS];
-    local float BSub[TS][TS];
+    local VALUE ASub[TS][TS];
+    local VALUE BSub[TS][TS];
 
-    float values[WPT];
+    VALUE values[WPT];
     for (int index = 0; index < WPT; index++) {
-        values[index] = 0.;
+        values[index] = ZERO;
     }
 
-    const int numTiles = N / TS;
+    const int numTiles = K / TS;
     for (int tile = 0; tile < numTiles; tile++) {
         for (int index = 0; index < WPT; index++) {
             const int tiledRow = TS * tile + row;
@@ -29,15 +31,16 @@ kernel void multiply(
             const int offset = index * RTS;
             const int subCol = col + offset;
 
-            ASub[subCol][row] = A[(tiledCol + offset) * N + globalRow];
-            BSub[subCol][row] = B[(globalCol + offset) * N + tiledRow];
+            ASub[subCol][row] = A[(tiledCol + offset) * M + globalRow];
+            BSub[subCol][row] = B[(globalCol + offset) * K + tiledRow];
         }
 
         barrier(CLK_LOCAL_MEM_FENCE);
 
         for (int k = 0; k < TS; k++) {
             for (int index = 0; index < WPT; index++) {
-                values[index] += ASub[k][row] * BSub[col + index * RTS][k];
+                values[index] =
+                    ADD(values[index], MUL(ASub[k][row], BSub[col + index * RTS][k]));
             }
         }
 
@@ -45,6 +48,6 @@ kernel void multiply(
     }
 
     for (int index = 0; index < WPT; index++) {
-        C[(globalCol + index * RTS) * N + globalRow] = values[index];
+        C[(globalCol + index * RTS) * M + globalRow] = values[index];
     }
 }
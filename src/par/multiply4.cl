@@ -0,0 +1,65 @@
+kernel void multiply(
+    const int M,
+    const int N,
+    const int K,
+    const global VALUE* A,
+    const global VALUE* B,
+    global VALUE* C
+) {
+    const int TS = 32;
+    const int WPT = 4;
+    const int RTS = TS / WPT;
+
+    const int row = get_local_id(0);
+    const int col = get_local_id(1);
+    const int offsetRow = TS * get_group_id(0);
+    const int offsetCol = TS * get_group_id(1);
+
+    local VALUE ASub[TS][TS];
+    local VALUE BSub[TS][TS];
+
+    VALUE values[WPT][WPT];
+    for (int indexRow = 0; indexRow < WPT; indexRow++) {
+        for (int indexCol = 0; indexCol < WPT; indexCol++) {
+            values[indexRow][indexCol] = ZERO;
+        }
+    }
+
+    VALUE ARegs[WPT];
+    const int numTiles = K / TS;
+    for (int tile = 0; tile < numTiles; tile++) {
+        for (int indexRow = 0; indexRow < WPT; indexRow++) {
+            for (int indexCol = 0; indexCol < WPT; indexCol++) {
+                const int subRow = row + indexRow * RTS;
+                const int subCol = col + indexCol * RTS;
+
+                ASub[subCol][subRow] = A[(TS * tile + subCol) * M + offsetRow + subRow];
+                BSub[subCol][subRow] = B[(offsetCol + subCol) * K + TS * tile + subRow];
+            }
+        }
+
+        barrier(CLK_LOCAL_MEM_FENCE);
+
+        for (int k = 0; k < TS; k++) {
+            for (int indexRow = 0; indexRow < WPT; indexRow++) {
+                ARegs[indexRow] = ASub[k][row + indexRow * RTS];
+            }
+            for (int indexCol = 0; indexCol < WPT; indexCol++) {
+                const VALUE BReg = BSub[col + indexCol * RTS][k];
+                for (int indexRow = 0; indexRow < WPT; indexRow++) {
+                    values[indexRow][indexCol] =
+                        ADD(values[indexRow][indexCol], MUL(ARegs[indexRow], BReg));
+                }
+            }
+        }
+
+        barrier(CLK_LOCAL_MEM_FENCE);
+    }
+
+    for (int indexRow = 0; indexRow < WPT; indexRow++) {
+        for (int indexCol = 0; indexCol < WPT; indexCol++) {
+            C[(offsetCol + col + indexCol * RTS) * M + offsetRow + row + indexRow * RTS]
+                = values[indexRow][indexCol];
+        }
+    }
+}
@@ -0,0 +1,48 @@
+#define vloadW CONCAT(vload, WIDTH)
+#define vstoreW CONCAT(vstore, WIDTH)
+
+kernel void multiply(
+    const int M,
+    const int N,
+    const int K,
+    const global VALUE* A,
+    const global VALUE* B,
+    global VALUE* C
+) {
+    const int TS = 32;
+
+    const int row = get_local_id(0);
+    const int col = get_local_id(1);
+    const int offsetRow = TS * get_group_id(0);
+    const int globalCol = TS * get_group_id(1) + col;
+    const int subRow = row * WIDTH;
+
+    local VALUE ASub[TS][TS];
+    local VALUE BSub[TS][TS];
+
+    VALUE values[WIDTH];
+    for (int index = 0; index < WIDTH; index++) {
+        values[index] = ZERO;
+    }
+
+    const int numTiles = K / TS;
+    for (int tile = 0; tile < numTiles; tile++) {
+        const int tiledCol = TS * tile + col;
+
+        vstoreW(vloadW(0, A + tiledCol * M + offsetRow + subRow), 0, &ASub[col][subRow]);
+        vstoreW(vloadW(0, B + globalCol * K + TS * tile + subRow), 0, &BSub[col][subRow]);
+
+        barrier(CLK_LOCAL_MEM_FENCE);
+
+        for (int k = 0; k < TS; k++) {
+            const VALUE BReg = BSub[col][k];
+            for (int index = 0; index < WIDTH; index++) {
+                values[index] = ADD(values[index], MUL(ASub[k][subRow + index], BReg));
+            }
+        }
+
+        barrier(CLK_LOCAL_MEM_FENCE);
+    }
+
+    vstoreW(vloadW(0, values), 0, C + globalCol * M + offsetRow + subRow);
+}
@@ -0,0 +1,49 @@
+kernel void transpose(
+    const int rows,
+    const int cols,
+    const global VALUE* input,
+    global VALUE* output
+) {
+    const int row = get_global_id(0);
+    const int col = get_global_id(1);
+
+    output[row * cols + col] = input[col * rows + row];
+}
+
+kernel void multiply(
+    const int M,
+    const int N,
+    const int K,
+    const global VALUE* A,
+    const global VALUE* BT,
+    global VALUE* C
+) {
+    const int TS = 16;
+
+    const int row = get_local_id(0);
+    const int col = get_local_id(1);
+    const int globalRow = TS * get_group_id(0) + row;
+    const int offsetCol = TS * get_group_id(1);
+
+    local VALUE ASub[TS][TS];
+    local VALUE BSub[TS][TS];
+
+    VALUE value = ZERO;
+    const int numTiles = K / TS;
+    for (int tile = 0; tile < numTiles; tile++) {
+        const int tiledCol = TS * tile + col;
+
+        ASub[col][row] = A[tiledCol * M + globalRow];
+        BSub[col][row] = BT[tiledCol * N + offsetCol + row];
+
+        barrier(CLK_LOCAL_MEM_FENCE);
+
+        for (int k = 0; k < TS; k++) {
+            value = ADD(value, MUL(ASub[k][row], BSub[k][col]));
+        }
+
+        barrier(CLK_LOCAL_MEM_FENCE);
+    }
+
+    C[(offsetCol + col) * M + globalRow] = value;
+}
@@ -0,0 +1,40 @@
+kernel void multiply(
+    const int M,
+    const int N,
+    const int K,
+    const global VALUE* A,
+    const global VALUE* B,
+    global VALUE* C
+) {
+    const int TS = 16;
+    // Shifts consecutive rows of `BSub` to different banks.
+    const int PAD = 1;
+
+    const int row = get_local_id(0);
+    const int col = get_local_id(1);
+    const int globalRow = TS * get_group_id(0) + row;
+    const int globalCol = TS * get_group_id(1) + col;
+
+    local VALUE ASub[TS][TS];
+    local VALUE BSub[TS][TS + PAD];
+
+    VALUE value = ZERO;
+    const int numTiles = K / TS;
+    for (int tile = 0; tile < numTiles; tile++) {
+        const int tiledRow = TS * tile + row;
+        const int tiledCol = TS * tile + col;
+
+        ASub[col][row] = A[tiledCol * M + globalRow];
+        BSub[row][col] = B[globalCol * K + tiledRow];
+
+        barrier(CLK_LOCAL_MEM_FENCE);
+
+        for (int k = 0; k < TS; k++) {
+            value = ADD(value, MUL(ASub[k][row], BSub[k][col]));
+        }
+
+        barrier(CLK_LOCAL_MEM_FENCE);
+    }
+
+    C[globalCol * M + globalRow] = value;
+}
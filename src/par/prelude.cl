@@ -0,0 +1,17 @@
+#ifdef cl_khr_fp64
+#pragma OPENCL EXTENSION cl_khr_fp64 : enable
+#endif
+
+#define CONCAT_(a, b) a##b
+#define CONCAT(a, b) CONCAT_(a, b)
+
+// Semirings may override the arithmetic, e.g. to reduce by a modulus.
+#ifndef ZERO
+#define ZERO 0
+#endif
+#ifndef ADD
+#define ADD(a, b) ((a) + (b))
+#endif
+#ifndef MUL
+#define MUL(a, b) ((a) * (b))
+#endif
@@ -0,0 +1,176 @@
+//! Precomputed partial products answering arbitrary cyclic range queries.
+
+use crate::{
+    solver::Multiplier,
+    task::{Shape, Solution, Task, Window},
+};
+
+/// Structure answering queries for products of cyclic ranges of a chain.
+pub trait RangeQuery<M> {
+    /// The number of matrices in the chain.
+    fn n(&self) -> usize;
+
+    /// Product `A[start] · … · A[start + length - 1]` with indices taken modulo `N`,
+    /// e.g. a single rotation is a range of length `N`.
+    fn product<X: Multiplier<M>>(
+        &self,
+        multiplier: &mut X,
+        start: usize,
+        length: usize,
+    ) -> Result<M, X::Error>;
+
+    /// Products of all windows, e.g. [`Window::full`] gives the original problem's solution.
+    fn solve<X: Multiplier<M>>(
+        &self,
+        multiplier: &mut X,
+        window: Window,
+    ) -> Result<Solution<M>, X::Error> {
+        self.stream(multiplier, window)
+            .collect::<Result<_, _>>()
+            .map(Solution)
+    }
+
+    /// Products of the windows starting with `A[i]` for each `i` in `indices`, in that order.
+    fn solve_only<X: Multiplier<M>>(
+        &self,
+        multiplier: &mut X,
+        window: Window,
+        indices: impl IntoIterator<Item = usize>,
+    ) -> Result<Solution<M>, X::Error> {
+        self.stream_only(multiplier, window, indices)
+            .collect::<Result<_, _>>()
+            .map(Solution)
+    }
+
+    /// Like [`Self::solve`], but yields each window as soon as it is computed.
+    fn stream<'a, X: Multiplier<M>>(
+        &'a self,
+        multiplier: &'a mut X,
+        window: Window,
+    ) -> impl Iterator<Item = Result<M, X::Error>> + 'a {
+        self.stream_only(multiplier, window, 0..window.count(self.n()))
+    }
+
+    /// Like [`Self::solve_only`], but yields each window as soon as it is computed.
+    fn stream_only<'a, X: Multiplier<M>>(
+        &'a self,
+        multiplier: &'a mut X,
+        window: Window,
+        indices: impl IntoIterator<Item = usize, IntoIter: 'a>,
+    ) -> impl Iterator<Item = Result<M, X::Error>> + 'a {
+        indices
+            .into_iter()
+            .map(move |start| self.product(multiplier, start, window.length))
+    }
+}
+
+/// Disjoint sparse table over the chain repeated twice, so that cyclic ranges become contiguous.
+///
+/// Building it takes `O(N log N)` multiplications, after which the product of any range
+/// takes at most one multiplication.
+#[derive(Debug, Clone)]
+pub struct RangeProducts<M> {
+    matrices: Vec<M>,
+    /// On level `h` the sequence is split into blocks of `2^(h + 1)` matrices:
+    /// entries of a block's first half are products up to its middle,
+    /// entries of its second half are products from its middle.
+    levels: Vec<Vec<M>>,
+}
+
+impl<M: Clone + Shape> RangeProducts<M> {
+    pub fn new<X: Multiplier<M>>(multiplier: &mut X, task: &Task<M>) -> Result<Self, X::Error> {
+        let matrices = task.matrices().to_vec();
+        let n = matrices.len();
+        // Ranges start in `0..N` and are at most `N` matrices long.
+        let len = 2 * n - 1;
+        let at = |index: usize| &matrices[index % n];
+
+        let mut levels = Vec::new();
+        let mut half = 1;
+        while half < len {
+            let mut level = Vec::with_capacity(len);
+            for start in (0..len).step_by(2 * half) {
+                let middle = (start + half).min(len);
+                let end = (start + 2 * half).min(len);
+
+                let mut suffixes = vec![at(middle - 1).clone()];
+                for index in (start..middle - 1).rev() {
+                    suffixes.push(multiplier.multiply(at(index), suffixes.last().unwrap())?);
+                }
+                level.extend(suffixes.into_iter().rev());
+
+                if middle < end {
+                    level.push(at(middle).clone());
+                    for index in middle + 1..end {
+                        level.push(multiplier.multiply(level.last().unwrap(), at(index))?);
+                    }
+                }
+            }
+            levels.push(level);
+            half *= 2;
+        }
+
+        Ok(Self { matrices, levels })
+    }
+}
+
+impl<M: Clone + Shape> RangeQuery<M> for RangeProducts<M> {
+    fn n(&self) -> usize {
+        self.matrices.len()
+    }
+
+    fn product<X: Multiplier<M>>(
+        &self,
+        multiplier: &mut X,
+        start: usize,
+        length: usize,
+    ) -> Result<M, X::Error> {
+        let n = self.n();
+        assert!(
+            (1..=n).contains(&length),
+            "Range length {length} should be in 1..={n}"
+        );
+        let first = start % n;
+        let last = first + length - 1;
+        if first == last {
+            return Ok(self.matrices[first].clone());
+        }
+
+        // The highest differing bit is the level on which the range crosses a block's middle.
+        let level = &self.levels[(first ^ last).ilog2() as usize];
+        multiplier.multiply(&level[first], &level[last])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq::Cpu, solver};
+
+    #[test]
+    fn ranges_match_direct() {
+        for n in 1..=9_usize {
+            let task = Task::chain(n, |i| [i64::from(i), 1, 2 * i64::from(i) + 1, -1]);
+            let ranges = RangeProducts::new(&mut Cpu, &task).unwrap();
+
+            for start in 0..n {
+                for length in 1..=n {
+                    assert_eq!(
+                        ranges.product(&mut Cpu, start, length),
+                        Cpu.multiply_all(solver::window_matrices(
+                            &task,
+                            Window::cyclic(length),
+                            start
+                        ))
+                        .map(Option::unwrap),
+                        "n = {n}, start = {start}, length = {length}"
+                    );
+                }
+            }
+            assert_eq!(
+                ranges.solve(&mut Cpu, Window::full(&task)),
+                solver::solve(&mut Cpu, &task, Window::full(&task))
+            );
+        }
+    }
+}
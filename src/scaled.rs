@@ -0,0 +1,126 @@
+//! Normalized representation of products which would otherwise overflow.
+
+use opencl3::types::{cl_double, cl_float};
+
+use crate::{
+    semiring::Semiring,
+    solver::Representation,
+    task::{Matrix, Shape},
+};
+
+/// Floating-point values, so that matrices of them can be rescaled by powers of two.
+pub trait Float: Semiring {
+    fn from_f64(value: f64) -> Self;
+}
+
+impl Float for cl_float {
+    fn from_f64(value: f64) -> Self {
+        value as Self
+    }
+}
+
+impl Float for cl_double {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+/// Matrix `2^exponent · matrix`, where the largest magnitude of the entries is kept in `[1, 2)`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scaled<T> {
+    matrix: Matrix<T>,
+    exponent: i32,
+}
+
+impl<T: Float> Scaled<T> {
+    pub fn new(matrix: Matrix<T>) -> Self {
+        Self::normalize(matrix, 0)
+    }
+
+    pub fn matrix(&self) -> &Matrix<T> {
+        &self.matrix
+    }
+
+    pub const fn exponent(&self) -> i32 {
+        self.exponent
+    }
+
+    /// Matrix `2^exponent · matrix` rescaled so that its largest magnitude is in `[1, 2)`.
+    pub fn normalize(mut matrix: Matrix<T>, exponent: i32) -> Self {
+        let max = matrix
+            .as_slice()
+            .iter()
+            .map(|value| value.to_f64().abs())
+            .fold(0., f64::max);
+        // There is nothing to normalize in zero matrices, and NaN or infinity cannot be rescaled.
+        if max == 0. || !max.is_finite() {
+            return Self { matrix, exponent };
+        }
+
+        let shift = max.log2().floor() as i32;
+        for index in 0..matrix.rows() * matrix.cols() {
+            matrix[index] = T::from_f64(ldexp(matrix[index].to_f64(), -shift));
+        }
+        Self {
+            matrix,
+            exponent: exponent + shift,
+        }
+    }
+}
+
+/// Computes `value · 2^exponent` exactly unless the result is subnormal.
+fn ldexp(value: f64, exponent: i32) -> f64 {
+    // Powers of two of subnormal values' exponents are beyond `f64`, so they are applied in halves.
+    let half = exponent / 2;
+    value * 2f64.powi(half) * 2f64.powi(exponent - half)
+}
+
+impl<T> Shape for Scaled<T> {
+    fn rows(&self) -> usize {
+        self.matrix.rows()
+    }
+
+    fn cols(&self) -> usize {
+        self.matrix.cols()
+    }
+}
+
+/// Products are normalized on the host, e.g. once they are read back from the device.
+impl<T: Float> Representation for Scaled<T> {
+    type Value = T;
+
+    fn product<E>(
+        a: &Self,
+        b: &Self,
+        multiply: impl FnOnce(&Matrix<T>, &Matrix<T>) -> Result<Matrix<T>, E>,
+    ) -> Result<Self, E> {
+        let matrix = multiply(&a.matrix, &b.matrix)?;
+        Ok(Self::normalize(matrix, a.exponent + b.exponent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seq::Cpu, solver::Multiplier};
+
+    #[test]
+    fn does_not_overflow() {
+        let matrix = Scaled::new(Matrix::from_vec(vec![3e30_f32, 1., 0., 1e-30]).unwrap());
+        assert_eq!(matrix.exponent(), 101);
+
+        let product = Cpu
+            .multiply_all((0..8).map(|_| matrix.clone()))
+            .unwrap()
+            .unwrap();
+        assert!(product
+            .matrix()
+            .as_slice()
+            .iter()
+            .all(|value| value.is_finite()));
+        // `(3e30)^8` is about `2^809.94`.
+        assert_eq!(product.exponent(), 809);
+        assert!((1. ..2.).contains(&product.matrix()[0]));
+    }
+}
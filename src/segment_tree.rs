@@ -0,0 +1,192 @@
+//! Partial products which are maintained while matrices of a task change.
+
+use std::mem;
+
+use crate::{
+    ranges::RangeQuery,
+    solver::Multiplier,
+    task::{self, Shape, Task},
+};
+
+/// Segment tree whose nodes are products of the matrices they cover.
+///
+/// Replacing a matrix takes `O(log N)` multiplications,
+/// and so does computing the product of any cyclic range.
+#[derive(Debug, Clone)]
+pub struct SegmentTree<M> {
+    n: usize,
+    /// Node `i` is the product of its children `2i` and `2i + 1`,
+    /// and leaves start at `nodes.len() / 2`.
+    /// Missing nodes cover no matrices and act as identities.
+    nodes: Vec<Option<M>>,
+}
+
+impl<M: Clone + Shape> SegmentTree<M> {
+    pub fn new<X: Multiplier<M>>(multiplier: &mut X, task: &Task<M>) -> Result<Self, X::Error> {
+        let n = task.matrices().len();
+        let leaves = n.next_power_of_two();
+
+        let mut nodes = vec![None; 2 * leaves];
+        for (node, matrix) in nodes[leaves..].iter_mut().zip(task.matrices()) {
+            *node = Some(matrix.clone());
+        }
+        let mut tree = Self { n, nodes };
+        for node in (1..leaves).rev() {
+            tree.refresh(multiplier, node)?;
+        }
+
+        Ok(tree)
+    }
+
+    /// Replaces the leaf of `A[index]` with a matrix of the same dimensions,
+    /// returning the previous one, and recomputes the `⌈log₂ N⌉` nodes on the leaf's path
+    /// to the root with one multiplication each.
+    ///
+    /// If a multiplication fails, its node and the ones above it are left stale.
+    pub fn update<X: Multiplier<M>>(
+        &mut self,
+        multiplier: &mut X,
+        index: usize,
+        matrix: M,
+    ) -> Result<M, X::Error> {
+        assert!(index < self.n, "Index {index} is out of 0..{}", self.n);
+        let mut node = self.nodes.len() / 2 + index;
+        let previous = self.nodes[node]
+            .as_ref()
+            .expect("Leaves within the chain are present");
+        task::assert_replaceable(previous, &matrix);
+        let previous = self.nodes[node].replace(matrix).unwrap();
+
+        while node > 1 {
+            node /= 2;
+            self.refresh(multiplier, node)?;
+        }
+
+        Ok(previous)
+    }
+
+    /// Product of `A[start..end]`.
+    fn query<X: Multiplier<M>>(
+        &self,
+        multiplier: &mut X,
+        start: usize,
+        end: usize,
+    ) -> Result<Option<M>, X::Error> {
+        let leaves = self.nodes.len() / 2;
+        let (mut start, mut end) = (start + leaves, end + leaves);
+        // The product is not commutative, so both sides are accumulated separately.
+        let (mut left, mut right) = (None, None);
+        while start < end {
+            if start % 2 == 1 {
+                left = combine(multiplier, left, self.nodes[start].clone())?;
+                start += 1;
+            }
+            if end % 2 == 1 {
+                end -= 1;
+                right = combine(multiplier, self.nodes[end].clone(), right)?;
+            }
+            start /= 2;
+            end /= 2;
+        }
+
+        combine(multiplier, left, right)
+    }
+
+    fn refresh<X: Multiplier<M>>(
+        &mut self,
+        multiplier: &mut X,
+        node: usize,
+    ) -> Result<(), X::Error> {
+        let left = mem::take(&mut self.nodes[2 * node]);
+        let right = mem::take(&mut self.nodes[2 * node + 1]);
+        let product = match (&left, &right) {
+            (Some(left), Some(right)) => multiplier.multiply(left, right).map(Some),
+            (Some(only), None) | (None, Some(only)) => Ok(Some(only.clone())),
+            (None, None) => Ok(None),
+        };
+        self.nodes[2 * node] = left;
+        self.nodes[2 * node + 1] = right;
+        self.nodes[node] = product?;
+        Ok(())
+    }
+}
+
+impl<M: Clone + Shape> RangeQuery<M> for SegmentTree<M> {
+    fn n(&self) -> usize {
+        self.n
+    }
+
+    fn product<X: Multiplier<M>>(
+        &self,
+        multiplier: &mut X,
+        start: usize,
+        length: usize,
+    ) -> Result<M, X::Error> {
+        let n = self.n;
+        assert!(
+            (1..=n).contains(&length),
+            "Range length {length} should be in 1..={n}"
+        );
+        let start = start % n;
+        let end = start + length;
+
+        let product = if end <= n {
+            self.query(multiplier, start, end)?
+        } else {
+            let head = self.query(multiplier, start, n)?;
+            let tail = self.query(multiplier, 0, end - n)?;
+            combine(multiplier, head, tail)?
+        };
+        Ok(product.expect("Ranges are not empty"))
+    }
+}
+
+/// Multiplies optional matrices, where missing ones are identities.
+fn combine<M, X: Multiplier<M>>(
+    multiplier: &mut X,
+    left: Option<M>,
+    right: Option<M>,
+) -> Result<Option<M>, X::Error> {
+    match (left, right) {
+        (Some(left), Some(right)) => multiplier.multiply(&left, &right).map(Some),
+        (left, right) => Ok(left.or(right)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        seq::Cpu,
+        solver,
+        task::{Matrix, Window},
+    };
+
+    #[test]
+    fn updates_match_direct() {
+        let matrix = |i: i64| Matrix::from_vec(vec![i, 1, 2 * i + 1, -1]).unwrap();
+        for n in 1..=7 {
+            let mut task = Task::from_vec((0..n).map(matrix).collect()).unwrap();
+            let mut tree = SegmentTree::new(&mut Cpu, &task).unwrap();
+
+            for index in 0..n {
+                let replacement = matrix(index * index - 3);
+                tree.update(&mut Cpu, index as usize, replacement.clone())
+                    .unwrap();
+                task.replace(index as usize, replacement);
+
+                for window in [
+                    Window::full(&task),
+                    Window::linear(1),
+                    Window::cyclic(n.min(2) as usize),
+                ] {
+                    assert_eq!(
+                        tree.solve(&mut Cpu, window),
+                        solver::solve(&mut Cpu, &task, window),
+                        "n = {n}, index = {index}, {window:?}"
+                    );
+                }
+            }
+        }
+    }
+}
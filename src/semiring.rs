@@ -0,0 +1,491 @@
+//! Algebraic structures over which matrix products are computed.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    iter,
+};
+
+use opencl3::types::{cl_double, cl_float, cl_int, cl_long, cl_uchar, cl_uint, cl_ulong};
+use rand::Rng;
+
+use crate::{
+    simd::{self, Dot},
+    types::{Modular, Scalar},
+};
+
+/// Semiring whose operations are used to multiply matrices.
+///
+/// The kernels are specialized for a semiring by prepending [`Semiring::cl_definitions`]
+/// which may override `ZERO`, `ADD` and `MUL` macros defaulting to the usual arithmetic.
+pub trait Semiring: Scalar {
+    /// Additive identity which is also used to pad matrices.
+    const ZERO: Self;
+    /// Multiplicative identity.
+    const ONE: Self;
+
+    fn add(self, rhs: Self) -> Self;
+
+    fn mul(self, rhs: Self) -> Self;
+
+    /// OpenCL C definitions of the operations.
+    fn cl_definitions() -> String {
+        String::new()
+    }
+
+    /// Kernel computing sums of the products `a[i] · b[i]`,
+    /// which is explicitly vectorized for some types and should be looked up once per product.
+    fn dot() -> Dot<Self> {
+        dot
+    }
+}
+
+fn dot<T: Semiring>(a: &[T], b: &[T]) -> T {
+    iter::zip(a, b).fold(T::ZERO, |sum, (&a, &b)| sum.add(a.mul(b)))
+}
+
+/// Semiring with additive inverses, which Strassen's algorithm relies on.
+pub trait Ring: Semiring {
+    fn neg(self) -> Self;
+}
+
+impl Semiring for cl_float {
+    const ZERO: Self = 0.;
+    const ONE: Self = 1.;
+
+    fn add(self, rhs: Self) -> Self {
+        self + rhs
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        self * rhs
+    }
+
+    fn dot() -> Dot<Self> {
+        simd::dot_f32()
+    }
+}
+
+impl Semiring for cl_double {
+    const ZERO: Self = 0.;
+    const ONE: Self = 1.;
+
+    fn add(self, rhs: Self) -> Self {
+        self + rhs
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        self * rhs
+    }
+
+    fn dot() -> Dot<Self> {
+        simd::dot_f64()
+    }
+}
+
+// Integer arithmetic wraps on overflow so that CPU results match the device ones.
+
+impl Semiring for cl_int {
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+
+    fn add(self, rhs: Self) -> Self {
+        self.wrapping_add(rhs)
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        self.wrapping_mul(rhs)
+    }
+}
+
+impl Semiring for cl_long {
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+
+    fn add(self, rhs: Self) -> Self {
+        self.wrapping_add(rhs)
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        self.wrapping_mul(rhs)
+    }
+}
+
+impl<const P: cl_uint> Semiring for Modular<P> {
+    const ZERO: Self = Self::new(0);
+    const ONE: Self = Self::new(1);
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(cl_ulong::from(self.get()) + cl_ulong::from(rhs.get()))
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(cl_ulong::from(self.get()) * cl_ulong::from(rhs.get()))
+    }
+
+    fn cl_definitions() -> String {
+        // Both sums and products of two residues fit into 64 bits before the reduction.
+        format!(
+            "#define MODULUS {P}UL\n\
+             #define ADD(a, b) ((VALUE) (((ulong) (a) + (b)) % MODULUS))\n\
+             #define MUL(a, b) ((VALUE) (((ulong) (a) * (b)) % MODULUS))\n"
+        )
+    }
+}
+
+impl Ring for cl_float {
+    fn neg(self) -> Self {
+        -self
+    }
+}
+
+impl Ring for cl_double {
+    fn neg(self) -> Self {
+        -self
+    }
+}
+
+impl Ring for cl_int {
+    fn neg(self) -> Self {
+        self.wrapping_neg()
+    }
+}
+
+impl Ring for cl_long {
+    fn neg(self) -> Self {
+        self.wrapping_neg()
+    }
+}
+
+impl<const P: cl_uint> Ring for Modular<P> {
+    fn neg(self) -> Self {
+        Self::new((P - self.get()).into())
+    }
+}
+
+/// Ordered scalar with infinities over which tropical semirings are defined.
+pub trait Tropical: Scalar {
+    const INFINITY: Self;
+    const NEG_INFINITY: Self;
+    /// The usual zero which is the multiplicative identity of tropical semirings.
+    const NEUTRAL: Self;
+    /// Name of the OpenCL C type ordered the same way as `Self`.
+    const CL_ORDERED_TYPE: &'static str;
+    const CL_INFINITY: &'static str;
+    const CL_NEG_INFINITY: &'static str;
+    /// OpenCL C expression of `a + b` saturating instead of overflowing.
+    const CL_SATURATING_ADD: &'static str;
+
+    fn saturating_add(self, rhs: Self) -> Self;
+}
+
+impl Tropical for cl_float {
+    const INFINITY: Self = Self::INFINITY;
+    const NEG_INFINITY: Self = Self::NEG_INFINITY;
+    const NEUTRAL: Self = 0.;
+    const CL_ORDERED_TYPE: &'static str = "float";
+    const CL_INFINITY: &'static str = "INFINITY";
+    const CL_NEG_INFINITY: &'static str = "-INFINITY";
+    const CL_SATURATING_ADD: &'static str = "((a) + (b))";
+
+    fn saturating_add(self, rhs: Self) -> Self {
+        self + rhs
+    }
+}
+
+impl Tropical for cl_double {
+    const INFINITY: Self = Self::INFINITY;
+    const NEG_INFINITY: Self = Self::NEG_INFINITY;
+    const NEUTRAL: Self = 0.;
+    const CL_ORDERED_TYPE: &'static str = "double";
+    const CL_INFINITY: &'static str = "INFINITY";
+    const CL_NEG_INFINITY: &'static str = "-INFINITY";
+    const CL_SATURATING_ADD: &'static str = "((a) + (b))";
+
+    fn saturating_add(self, rhs: Self) -> Self {
+        self + rhs
+    }
+}
+
+impl Tropical for cl_int {
+    const INFINITY: Self = Self::MAX;
+    const NEG_INFINITY: Self = Self::MIN;
+    const NEUTRAL: Self = 0;
+    const CL_ORDERED_TYPE: &'static str = "int";
+    const CL_INFINITY: &'static str = "INT_MAX";
+    const CL_NEG_INFINITY: &'static str = "INT_MIN";
+    const CL_SATURATING_ADD: &'static str = "add_sat(a, b)";
+
+    fn saturating_add(self, rhs: Self) -> Self {
+        self.saturating_add(rhs)
+    }
+}
+
+impl Tropical for cl_long {
+    const INFINITY: Self = Self::MAX;
+    const NEG_INFINITY: Self = Self::MIN;
+    const NEUTRAL: Self = 0;
+    const CL_ORDERED_TYPE: &'static str = "long";
+    const CL_INFINITY: &'static str = "LONG_MAX";
+    const CL_NEG_INFINITY: &'static str = "LONG_MIN";
+    const CL_SATURATING_ADD: &'static str = "add_sat(a, b)";
+
+    fn saturating_add(self, rhs: Self) -> Self {
+        self.saturating_add(rhs)
+    }
+}
+
+/// Min-plus semiring where `+` is `min` and `×` is `+`, e.g. for shortest paths.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct MinPlus<T>(pub T);
+
+/// Max-plus semiring where `+` is `max` and `×` is `+`, e.g. for longest paths.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct MaxPlus<T>(pub T);
+
+impl<T: Display> Display for MinPlus<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<T: Display> Display for MaxPlus<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+unsafe impl<T: Tropical> Scalar for MinPlus<T> {
+    const CL_TYPE: &'static str = T::CL_ORDERED_TYPE;
+    const CL_EXTENSION: Option<&'static str> = T::CL_EXTENSION;
+    const EXACT: bool = T::EXACT;
+
+    fn from_u8(value: u8) -> Self {
+        Self(T::from_u8(value))
+    }
+
+    fn to_f64(self) -> f64 {
+        self.0.to_f64()
+    }
+
+    /// Infinities are the semiring's zero rather than a result of an overflow.
+    fn is_finite(self) -> bool {
+        !self.0.to_f64().is_nan()
+    }
+
+    fn random(rng: &mut impl Rng) -> Self {
+        Self(T::random(rng))
+    }
+}
+
+unsafe impl<T: Tropical> Scalar for MaxPlus<T> {
+    const CL_TYPE: &'static str = T::CL_ORDERED_TYPE;
+    const CL_EXTENSION: Option<&'static str> = T::CL_EXTENSION;
+    const EXACT: bool = T::EXACT;
+
+    fn from_u8(value: u8) -> Self {
+        Self(T::from_u8(value))
+    }
+
+    fn to_f64(self) -> f64 {
+        self.0.to_f64()
+    }
+
+    /// Infinities are the semiring's zero rather than a result of an overflow.
+    fn is_finite(self) -> bool {
+        !self.0.to_f64().is_nan()
+    }
+
+    fn random(rng: &mut impl Rng) -> Self {
+        Self(T::random(rng))
+    }
+}
+
+impl<T: Tropical> Semiring for MinPlus<T> {
+    const ZERO: Self = Self(T::INFINITY);
+    const ONE: Self = Self(T::NEUTRAL);
+
+    fn add(self, rhs: Self) -> Self {
+        if rhs.0 < self.0 {
+            rhs
+        } else {
+            self
+        }
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        // Infinity is absorbing even when added to negative values.
+        if self == Self::ZERO || rhs == Self::ZERO {
+            Self::ZERO
+        } else {
+            Self(self.0.saturating_add(rhs.0))
+        }
+    }
+
+    fn cl_definitions() -> String {
+        format!(
+            "#define ZERO {}\n\
+             #define ADD(a, b) min(a, b)\n\
+             #define MUL(a, b) ((a) == ZERO || (b) == ZERO ? ZERO : {})\n",
+            T::CL_INFINITY,
+            T::CL_SATURATING_ADD,
+        )
+    }
+}
+
+impl<T: Tropical> Semiring for MaxPlus<T> {
+    const ZERO: Self = Self(T::NEG_INFINITY);
+    const ONE: Self = Self(T::NEUTRAL);
+
+    fn add(self, rhs: Self) -> Self {
+        if rhs.0 > self.0 {
+            rhs
+        } else {
+            self
+        }
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        if self == Self::ZERO || rhs == Self::ZERO {
+            Self::ZERO
+        } else {
+            Self(self.0.saturating_add(rhs.0))
+        }
+    }
+
+    fn cl_definitions() -> String {
+        format!(
+            "#define ZERO {}\n\
+             #define ADD(a, b) max(a, b)\n\
+             #define MUL(a, b) ((a) == ZERO || (b) == ZERO ? ZERO : {})\n",
+            T::CL_NEG_INFINITY,
+            T::CL_SATURATING_ADD,
+        )
+    }
+}
+
+/// Boolean semiring where `+` is `or` and `×` is `and`, e.g. for reachability.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "bool", into = "bool")
+)]
+pub struct Boolean(cl_uchar);
+
+impl Boolean {
+    pub const FALSE: Self = Self(0);
+    pub const TRUE: Self = Self(1);
+
+    pub const fn new(value: bool) -> Self {
+        Self(value as cl_uchar)
+    }
+
+    pub const fn get(self) -> bool {
+        self.0 != 0
+    }
+}
+
+impl From<bool> for Boolean {
+    fn from(value: bool) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Boolean> for bool {
+    fn from(value: Boolean) -> Self {
+        value.get()
+    }
+}
+
+impl Display for Boolean {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+unsafe impl Scalar for Boolean {
+    const CL_TYPE: &'static str = "uchar";
+    const EXACT: bool = true;
+
+    fn from_u8(value: u8) -> Self {
+        Self::new(value != 0)
+    }
+
+    fn to_f64(self) -> f64 {
+        self.0.into()
+    }
+
+    fn random(rng: &mut impl Rng) -> Self {
+        // Sparse enough for products not to become full immediately.
+        Self::new(rng.gen_bool(0.125))
+    }
+}
+
+impl Semiring for Boolean {
+    const ZERO: Self = Self::FALSE;
+    const ONE: Self = Self::TRUE;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.get() || rhs.get())
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.get() && rhs.get())
+    }
+
+    fn cl_definitions() -> String {
+        "#define ADD(a, b) ((a) | (b))\n\
+         #define MUL(a, b) ((a) & (b))\n"
+            .to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Mersenne31;
+
+    use super::*;
+
+    #[test]
+    fn modular_arithmetic() {
+        let a = Mersenne31::new((1 << 31) - 2);
+        let b = Mersenne31::new(3);
+
+        assert_eq!(a.add(b), Mersenne31::new(2));
+        assert_eq!(a.mul(b), Mersenne31::new((1 << 31) - 4));
+        assert_eq!(a.mul(a), Mersenne31::ONE);
+    }
+
+    #[test]
+    fn tropical_infinity_is_absorbing() {
+        let infinity = MinPlus::<cl_int>::ZERO;
+
+        assert_eq!(infinity.mul(MinPlus(-5)), infinity);
+        assert_eq!(infinity.add(MinPlus(-5)), MinPlus(-5));
+        assert_eq!(MinPlus(3).mul(MinPlus(-5)), MinPlus(-2));
+        assert_eq!(MaxPlus(3).add(MaxPlus(-5)), MaxPlus(3));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let values = (Mersenne31::new(5), MinPlus(-2.5), Boolean::TRUE);
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[5,-2.5,true]");
+        assert_eq!(serde_json::from_str::<(_, _, _)>(&json).unwrap(), values);
+        assert!(serde_json::from_str::<Mersenne31>("2147483647").is_err());
+    }
+}
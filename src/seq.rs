@@ -1,68 +1,214 @@
+use std::{convert::Infallible, iter, ops::Mul};
+
 use rayon::prelude::*;
 
 use crate::{
-    task::{Matrix, Solution, Task},
-    types::ZERO,
+    blocked::{self, Blocked, ParallelBlocked},
+    semiring::Semiring,
+    solver::{self, Multiplier, Representation},
+    task::{Matrix, Shape, Solution, Task, Window},
 };
 
-pub fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
-    assert!(a.n() == b.n(), "matrices should have the same dimensions");
-    let n = a.n();
+/// Multiplier running on the current thread.
+#[derive(Debug, Clone, Copy)]
+pub struct Cpu;
+impl<M: Representation> Multiplier<M> for Cpu {
+    type Error = Infallible;
+
+    fn multiply(&mut self, a: &M, b: &M) -> Result<M, Infallible> {
+        M::product(a, b, |a, b| Ok(a * b))
+    }
+}
 
-    let a = a.as_slice();
-    let b = b.as_slice();
+pub fn multiply<T: Semiring>(a: &Matrix<T>, b: &Matrix<T>) -> Matrix<T> {
+    assert_multipliable(a, b);
+    Matrix::from_fn(a.rows(), b.cols(), |row, col| {
+        iter::zip(a.row(row), b.col(col)).fold(T::ZERO, |sum, (&a, &b)| sum.add(a.mul(b)))
+    })
+}
 
-    let mut c = vec![ZERO; n * n];
+impl<T: Semiring> Mul for &Matrix<T> {
+    type Output = Matrix<T>;
 
-    for row in 0..n {
-        for column in 0..n {
-            let mut value = 0.;
-            for k in 0..n {
-                value += a[k * n + row] * b[column * n + k];
-            }
-            c[column * n + row] = value;
-        }
+    fn mul(self, rhs: Self) -> Self::Output {
+        multiply(self, rhs)
     }
+}
 
-    c.into_boxed_slice()
-        .try_into()
-        .unwrap_or_else(|_| panic!("n = {n}"))
+pub fn assert_multipliable(a: &impl Shape, b: &impl Shape) {
+    assert!(
+        a.cols() == b.rows(),
+        "matrices of dimensions {}x{} and {}x{} cannot be multiplied",
+        a.rows(),
+        a.cols(),
+        b.rows(),
+        b.cols()
+    );
 }
 
-pub fn solve(task: &Task) -> Solution {
-    let n = task.matrices().len();
+pub fn solve_memoizing<M: Clone + Shape + Representation>(
+    task: &Task<M>,
+    window: Window,
+) -> Solution<M> {
+    let Ok(solution) = solver::solve_memoizing(&mut Cpu, task, window);
+    solution
+}
 
-    Solution(
-        (0..n)
-            .map(|index| {
-                multiply_all(task.matrices().iter().cycle().skip(index).take(n).cloned())
-                    .expect("This is unrechable when `n` is zero")
-            })
-            .collect(),
-    )
+pub fn solve_only<M: Clone + Shape + Representation>(
+    task: &Task<M>,
+    window: Window,
+    indices: &[usize],
+) -> Solution<M> {
+    let Ok(solution) = solver::solve_only(&mut Cpu, task, window, indices);
+    solution
 }
 
-pub fn solve_par(task: &Task) -> Solution {
-    let n = task.matrices().len();
+/// Computes the windows directly on multiple threads, each multiplying with a copy of `cpu`.
+pub fn solve_par<M: Clone + Shape + Send + Sync>(
+    cpu: impl Multiplier<M, Error = Infallible> + Copy + Send + Sync,
+    task: &Task<M>,
+    window: Window,
+) -> Solution<M> {
+    Solution(stream_par(cpu, task, window).collect())
+}
 
-    Solution(
-        (0..n)
-            .into_par_iter()
-            .map(|index| {
-                multiply_all(task.matrices().iter().cycle().skip(index).take(n).cloned())
-                    .expect("This is unrechable when `n` is zero")
-            })
-            .collect(),
+/// Computes only the windows starting with `A[i]` for each `i` in `indices` directly.
+pub fn solve_par_only<M: Clone + Shape + Send + Sync>(
+    cpu: impl Multiplier<M, Error = Infallible> + Copy + Send + Sync,
+    task: &Task<M>,
+    window: Window,
+    indices: &[usize],
+) -> Solution<M> {
+    Solution(stream_par_only(cpu, task, window, indices).collect())
+}
+
+/// Like [`solve_par`], but the windows can be consumed as soon as they are computed,
+/// e.g. by [`ParallelIterator::for_each`] in any order.
+pub fn stream_par<'a, M: Clone + Shape + Send + Sync>(
+    cpu: impl Multiplier<M, Error = Infallible> + Copy + Send + Sync + 'a,
+    task: &'a Task<M>,
+    window: Window,
+) -> impl IndexedParallelIterator<Item = M> + 'a {
+    stream_par_at(
+        cpu,
+        task,
+        window,
+        (0..window.count(task.matrices().len())).into_par_iter(),
     )
 }
 
-pub fn multiply_all(matrices: impl Iterator<Item = Matrix>) -> Option<Matrix> {
-    matrices.reduce(|l, r| multiply(&l, &r))
+/// Like [`solve_par_only`], but the windows can be consumed as soon as they are computed.
+pub fn stream_par_only<'a, M: Clone + Shape + Send + Sync>(
+    cpu: impl Multiplier<M, Error = Infallible> + Copy + Send + Sync + 'a,
+    task: &'a Task<M>,
+    window: Window,
+    indices: &'a [usize],
+) -> impl IndexedParallelIterator<Item = M> + 'a {
+    stream_par_at(cpu, task, window, indices.par_iter().copied())
+}
+
+fn stream_par_at<'a, M: Clone + Shape + Send + Sync>(
+    cpu: impl Multiplier<M, Error = Infallible> + Copy + Send + Sync + 'a,
+    task: &'a Task<M>,
+    window: Window,
+    indices: impl IndexedParallelIterator<Item = usize> + 'a,
+) -> impl IndexedParallelIterator<Item = M> + 'a {
+    indices.map(move |index| {
+        let mut cpu = cpu;
+        let Ok(product) = cpu.multiply_all(solver::window_matrices(task, window, index));
+        product.expect("This is unrechable when `n` is zero")
+    })
+}
+
+/// How threads are shared between the windows and the multiplications within them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parallelism {
+    /// Windows are computed in parallel, each on a single thread.
+    Outer,
+    /// Windows are computed one by one, each multiplication on all threads.
+    Inner,
+    /// Windows are computed in parallel, and so are the column blocks of their multiplications,
+    /// which work stealing balances.
+    Mixed,
+}
+
+impl Parallelism {
+    /// Picks the parallelism for `windows` windows of matrices
+    /// with at most `dimension` rows and columns.
+    pub fn schedule(windows: usize, dimension: usize, threads: usize) -> Self {
+        // A multiplication cannot be split into more parallel jobs than its column blocks.
+        if windows >= threads || dimension <= blocked::BLOCK {
+            Self::Outer
+        } else if windows == 1 {
+            Self::Inner
+        } else {
+            Self::Mixed
+        }
+    }
+}
+
+/// Computes the windows directly with the cache-blocked multiplication.
+pub fn solve_scheduled<M: Clone + Shape + Send + Sync + Representation>(
+    parallelism: Parallelism,
+    task: &Task<M>,
+    window: Window,
+) -> Solution<M> {
+    match parallelism {
+        Parallelism::Outer => solve_par(Blocked, task, window),
+        Parallelism::Inner => {
+            let Ok(solution) = solver::solve(&mut ParallelBlocked, task, window);
+            solution
+        }
+        Parallelism::Mixed => solve_par(ParallelBlocked, task, window),
+    }
+}
+
+/// Computes only the windows starting with `A[i]` for each `i` in `indices` directly.
+pub fn solve_scheduled_only<M: Clone + Shape + Send + Sync + Representation>(
+    parallelism: Parallelism,
+    task: &Task<M>,
+    window: Window,
+    indices: &[usize],
+) -> Solution<M> {
+    match parallelism {
+        Parallelism::Outer => solve_par_only(Blocked, task, window, indices),
+        Parallelism::Inner => Solution(
+            indices
+                .iter()
+                .map(|&index| {
+                    let Ok(product) =
+                        ParallelBlocked.multiply_all(solver::window_matrices(task, window, index));
+                    product.expect("Windows are not empty")
+                })
+                .collect(),
+        ),
+        Parallelism::Mixed => solve_par_only(ParallelBlocked, task, window, indices),
+    }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use crate::task::TaskError;
+
+    /// Matrix with small non-negative entries, so that products are exact for every scalar type.
+    pub(crate) fn test_matrix<T: Semiring>(rows: usize, cols: usize) -> Matrix<T> {
+        let values = (0..rows * cols)
+            .map(|i| T::from_u8((i * i % 19) as u8))
+            .collect();
+        Matrix::new(rows, cols, values).unwrap()
+    }
+
+    /// Checks that `product` matches [`multiply`] for test matrices of dimensions `m x l` and `l x n`.
+    pub(crate) fn assert_matches_naive<T: Semiring>(
+        dimensions: &[(usize, usize, usize)],
+        mut product: impl FnMut(&Matrix<T>, &Matrix<T>) -> Matrix<T>,
+    ) {
+        for &(m, l, n) in dimensions {
+            let (a, b) = (test_matrix(m, l), test_matrix(l, n));
+            assert_eq!(product(&a, &b), multiply(&a, &b), "{m}x{l}x{n}");
+        }
+    }
 
     #[test]
     fn simple_2x2() {
@@ -74,4 +220,74 @@ mod tests {
             Matrix::from_vec(vec![19., 43., 22., 50.]).unwrap(),
         );
     }
+
+    #[test]
+    fn rectangular_chain() {
+        // 1x2, 2x3 and 3x1 matrices.
+        let a = Matrix::new(1, 2, vec![1, 2]).unwrap();
+        let b = Matrix::new(2, 3, vec![1, 0, 0, 1, 1, 1]).unwrap();
+        let c = Matrix::new(3, 1, vec![1, 2, 3]).unwrap();
+        let task = Task::from_vec(vec![a.clone(), b.clone(), c.clone()]).unwrap();
+        assert_eq!(
+            Task::from_vec(vec![a, c, b]).unwrap_err(),
+            TaskError::DimensionMismatch {
+                index: 0,
+                expected: 3,
+                found: 1
+            }
+        );
+        assert_eq!(
+            Task::<Matrix<i32>>::from_vec(vec![]).unwrap_err(),
+            TaskError::Empty
+        );
+
+        let window = Window::full(&task);
+        let solution = solver::solve(&mut Cpu, &task, window).unwrap();
+        assert_eq!(solution.0[0], Matrix::new(1, 1, vec![14]).unwrap());
+        assert_eq!(
+            (solution.0[1].rows(), solution.0[1].cols()),
+            (2, 2),
+            "B·C·A is 2x2"
+        );
+        assert_eq!(solution.0[2].rows(), 3);
+        assert_eq!(solve_memoizing(&task, window), solution);
+        assert_eq!(solve_par(Cpu, &task, window), solution);
+    }
+
+    #[test]
+    fn schedules() {
+        use Parallelism::*;
+
+        assert_eq!(Parallelism::schedule(16, 2048, 8), Outer);
+        assert_eq!(Parallelism::schedule(2, 16, 8), Outer);
+        assert_eq!(Parallelism::schedule(1, 2048, 8), Inner);
+        assert_eq!(Parallelism::schedule(2, 2048, 8), Mixed);
+
+        let task = Task::from_vec(
+            (0..3)
+                .map(|i| Matrix::new(2, 2, vec![i, 1, 2, i * i]).unwrap())
+                .collect(),
+        )
+        .unwrap();
+        let window = Window::full(&task);
+        let expected = solver::solve(&mut Cpu, &task, window).unwrap();
+        for parallelism in [Outer, Inner, Mixed] {
+            assert_eq!(solve_scheduled(parallelism, &task, window), expected);
+            assert_eq!(
+                solve_scheduled_only(parallelism, &task, window, &[2, 0]).0,
+                [expected.0[2].clone(), expected.0[0].clone()]
+            );
+        }
+    }
+
+    #[test]
+    fn integers_wrap() {
+        let a = Matrix::from_vec(vec![i32::MAX, 0, 0, 1]).unwrap();
+        let b = Matrix::from_vec(vec![2, 0, 0, 1]).unwrap();
+
+        assert_eq!(
+            multiply(&a, &b),
+            Matrix::from_vec(vec![-2, 0, 0, 1]).unwrap(),
+        );
+    }
 }
@@ -0,0 +1,182 @@
+//! Explicitly vectorized dot products of floating-point values.
+//!
+//! Instruction sets are detected once per process, falling back to scalar loops.
+//! On x86-64 AVX2 with FMA is the widest supported one,
+//! since AVX-512 intrinsics and target features are not stable on the pinned toolchain.
+
+use std::{
+    ops::{Add, Mul},
+    sync::OnceLock,
+};
+
+/// Kernel computing the sum of the products `a[i] · b[i]` of two slices of the same length.
+pub type Dot<T> = fn(&[T], &[T]) -> T;
+
+/// The widest kernel for `f32` values supported by the CPU.
+pub fn dot_f32() -> Dot<f32> {
+    static KERNEL: OnceLock<Dot<f32>> = OnceLock::new();
+
+    *KERNEL.get_or_init(|| {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            return |a, b| {
+                assert_eq!(a.len(), b.len(), "Vectors should have the same length");
+                // SAFETY: the required features are available.
+                unsafe { x86::dot_f32(a, b) }
+            };
+        }
+        #[cfg(target_arch = "aarch64")]
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            return |a, b| {
+                assert_eq!(a.len(), b.len(), "Vectors should have the same length");
+                // SAFETY: the required features are available.
+                unsafe { aarch64::dot_f32(a, b) }
+            };
+        }
+
+        scalar
+    })
+}
+
+/// The widest kernel for `f64` values supported by the CPU.
+pub fn dot_f64() -> Dot<f64> {
+    static KERNEL: OnceLock<Dot<f64>> = OnceLock::new();
+
+    *KERNEL.get_or_init(|| {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            return |a, b| {
+                assert_eq!(a.len(), b.len(), "Vectors should have the same length");
+                // SAFETY: the required features are available.
+                unsafe { x86::dot_f64(a, b) }
+            };
+        }
+        #[cfg(target_arch = "aarch64")]
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            return |a, b| {
+                assert_eq!(a.len(), b.len(), "Vectors should have the same length");
+                // SAFETY: the required features are available.
+                unsafe { aarch64::dot_f64(a, b) }
+            };
+        }
+
+        scalar
+    })
+}
+
+fn scalar<T: Copy + Default + Add<Output = T> + Mul<Output = T>>(a: &[T], b: &[T]) -> T {
+    assert_eq!(a.len(), b.len(), "Vectors should have the same length");
+    a.iter()
+        .zip(b)
+        .fold(T::default(), |sum, (&a, &b)| sum + a * b)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    /// # Safety
+    ///
+    /// AVX2 and FMA should be available, and the slices should have the same length.
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn dot_f32(a: &[f32], b: &[f32]) -> f32 {
+        const LANES: usize = 8;
+        let split = a.len() - a.len() % LANES;
+
+        let mut sum = _mm256_setzero_ps();
+        for index in (0..split).step_by(LANES) {
+            let a = _mm256_loadu_ps(a.as_ptr().add(index));
+            let b = _mm256_loadu_ps(b.as_ptr().add(index));
+            sum = _mm256_fmadd_ps(a, b, sum);
+        }
+        let mut lanes = [0.; LANES];
+        _mm256_storeu_ps(lanes.as_mut_ptr(), sum);
+
+        lanes.iter().sum::<f32>() + super::scalar(&a[split..], &b[split..])
+    }
+
+    /// # Safety
+    ///
+    /// AVX2 and FMA should be available, and the slices should have the same length.
+    #[target_feature(enable = "avx2,fma")]
+    pub unsafe fn dot_f64(a: &[f64], b: &[f64]) -> f64 {
+        const LANES: usize = 4;
+        let split = a.len() - a.len() % LANES;
+
+        let mut sum = _mm256_setzero_pd();
+        for index in (0..split).step_by(LANES) {
+            let a = _mm256_loadu_pd(a.as_ptr().add(index));
+            let b = _mm256_loadu_pd(b.as_ptr().add(index));
+            sum = _mm256_fmadd_pd(a, b, sum);
+        }
+        let mut lanes = [0.; LANES];
+        _mm256_storeu_pd(lanes.as_mut_ptr(), sum);
+
+        lanes.iter().sum::<f64>() + super::scalar(&a[split..], &b[split..])
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod aarch64 {
+    use std::arch::aarch64::*;
+
+    /// # Safety
+    ///
+    /// NEON should be available, and the slices should have the same length.
+    #[target_feature(enable = "neon")]
+    pub unsafe fn dot_f32(a: &[f32], b: &[f32]) -> f32 {
+        const LANES: usize = 4;
+        let split = a.len() - a.len() % LANES;
+
+        let mut sum = vdupq_n_f32(0.);
+        for index in (0..split).step_by(LANES) {
+            let a = vld1q_f32(a.as_ptr().add(index));
+            let b = vld1q_f32(b.as_ptr().add(index));
+            sum = vfmaq_f32(sum, a, b);
+        }
+
+        vaddvq_f32(sum) + super::scalar(&a[split..], &b[split..])
+    }
+
+    /// # Safety
+    ///
+    /// NEON should be available, and the slices should have the same length.
+    #[target_feature(enable = "neon")]
+    pub unsafe fn dot_f64(a: &[f64], b: &[f64]) -> f64 {
+        const LANES: usize = 2;
+        let split = a.len() - a.len() % LANES;
+
+        let mut sum = vdupq_n_f64(0.);
+        for index in (0..split).step_by(LANES) {
+            let a = vld1q_f64(a.as_ptr().add(index));
+            let b = vld1q_f64(b.as_ptr().add(index));
+            sum = vfmaq_f64(sum, a, b);
+        }
+
+        vaddvq_f64(sum) + super::scalar(&a[split..], &b[split..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_scalar() {
+        for len in 0..=20 {
+            let a: Vec<_> = (0..len).map(|i| f64::from(i) * 0.5 - 3.).collect();
+            let b: Vec<_> = (0..len).map(|i| f64::from(i * i % 7) + 0.25).collect();
+            assert!(
+                (dot_f64()(&a, &b) - scalar(&a, &b)).abs() < 1e-9,
+                "len = {len}"
+            );
+
+            let a: Vec<_> = a.iter().map(|&value| value as f32).collect();
+            let b: Vec<_> = b.iter().map(|&value| value as f32).collect();
+            assert!(
+                (dot_f32()(&a, &b) - scalar(&a, &b)).abs() < 1e-3,
+                "len = {len}"
+            );
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! Windows obtained from the previous ones by similarity transformations.
+//!
+//! Consecutive windows satisfy `W[i + 1] = A[i]⁻¹ · W[i] · A[i + length]`,
+//! so after reducing the first window each of the next ones takes an inversion
+//! and 2 multiplications, e.g. `2(N - 1)` multiplications for the full cyclic products.
+//! Rounding errors are amplified by the condition numbers of the inverted matrices
+//! and accumulate along the chain, which is why this is only experimental.
+
+use std::iter;
+
+use crate::{
+    scaled::{Float, Scaled},
+    solver::{self, Multiplier, Windows},
+    task::{Matrix, Shape, Solution, Task, Window},
+};
+
+/// Inverse of a matrix along with the matrix's condition number `‖A‖₁ · ‖A⁻¹‖₁`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inverse<M> {
+    pub matrix: M,
+    pub condition: f64,
+}
+
+/// Matrices which can be inverted on the host.
+pub trait Invert: Sized {
+    /// The inverse, or `None` if the matrix is singular or not square.
+    fn invert(&self) -> Option<Inverse<Self>>;
+}
+
+impl<T: Float> Invert for Scaled<T> {
+    fn invert(&self) -> Option<Inverse<Self>> {
+        // Scaling by `2^exponent` does not change the condition number.
+        let Inverse { matrix, condition } = self.matrix().invert()?;
+        Some(Inverse {
+            matrix: Scaled::normalize(matrix, -self.exponent()),
+            condition,
+        })
+    }
+}
+
+/// Inverts a square matrix through its LU decomposition with partial pivoting in `f64`.
+impl<T: Float> Invert for Matrix<T> {
+    fn invert(&self) -> Option<Inverse<Self>> {
+        let n = self.rows();
+        if self.cols() != n || n == 0 {
+            return None;
+        }
+
+        let values = Matrix::from_fn(n, n, |row, col| self[(row, col)].to_f64());
+        let inverse = Matrix::new(n, n, lu_inverse(n, values.as_slice().to_vec())?).unwrap();
+        let condition = norm(n, values.as_slice()) * norm(n, inverse.as_slice());
+        Some(Inverse {
+            matrix: Matrix::from_fn(n, n, |row, col| T::from_f64(inverse[(row, col)])),
+            condition,
+        })
+    }
+}
+
+/// Inverts a column-major `n × n` matrix, returning `None` if it is singular.
+fn lu_inverse(n: usize, mut lu: Vec<f64>) -> Option<Vec<f64>> {
+    let at = |row: usize, col: usize| col * n + row;
+
+    // `P · A = L · U`, where row `k` of `P · A` is row `permutation[k]` of `A`,
+    // `L` is unit lower triangular, and both are stored in place of `A`.
+    let mut permutation: Vec<_> = (0..n).collect();
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|&a, &b| lu[at(a, k)].abs().total_cmp(&lu[at(b, k)].abs()))
+            .unwrap();
+        let diagonal = lu[at(pivot, k)];
+        if diagonal == 0. || !diagonal.is_finite() {
+            return None;
+        }
+        if pivot != k {
+            for col in 0..n {
+                lu.swap(at(k, col), at(pivot, col));
+            }
+            permutation.swap(k, pivot);
+        }
+
+        for row in k + 1..n {
+            let factor = lu[at(row, k)] / diagonal;
+            lu[at(row, k)] = factor;
+            for col in k + 1..n {
+                lu[at(row, col)] -= factor * lu[at(k, col)];
+            }
+        }
+    }
+
+    // Each column `x` of the inverse solves `L · U · x = P · e`.
+    let mut inverse = vec![0.; n * n];
+    for (col, x) in inverse.chunks_mut(n).enumerate() {
+        for row in 0..n {
+            x[row] = if permutation[row] == col { 1. } else { 0. };
+            for k in 0..row {
+                x[row] -= lu[at(row, k)] * x[k];
+            }
+        }
+        for row in (0..n).rev() {
+            for k in row + 1..n {
+                x[row] -= lu[at(row, k)] * x[k];
+            }
+            x[row] /= lu[at(row, row)];
+        }
+    }
+
+    Some(inverse)
+}
+
+/// The largest absolute column sum of a column-major `n × n` matrix.
+fn norm(n: usize, values: &[f64]) -> f64 {
+    values
+        .chunks(n)
+        .map(|col| col.iter().map(|value| value.abs()).sum())
+        .fold(0., f64::max)
+}
+
+/// How well-conditioned the inverted matrices were.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Conditioning {
+    /// All the matrices were inverted, and this is the largest of their condition numbers.
+    Inverted(f64),
+    /// `A[index]` is singular or not square, or its condition number exceeds the limit,
+    /// so the windows were memoized instead.
+    Fallback { index: usize, condition: f64 },
+}
+
+/// Computes all windows, falling back to [`solver::solve_memoizing`]
+/// if a matrix cannot be inverted or its condition number exceeds `max_condition`.
+pub fn solve<M: Clone + Shape + Invert, X: Multiplier<M>>(
+    multiplier: &mut X,
+    task: &Task<M>,
+    window: Window,
+    max_condition: f64,
+) -> Result<(Solution<M>, Conditioning), X::Error> {
+    let (windows, conditioning) = stream(multiplier, task, window, max_condition);
+    Ok((Solution(windows.collect::<Result<_, _>>()?), conditioning))
+}
+
+/// Like [`solve`], but yields each window as soon as it is transformed,
+/// holding only the previous one. The matrices are inverted beforehand.
+pub fn stream<'a, M: Clone + Shape + Invert, X: Multiplier<M>>(
+    multiplier: &'a mut X,
+    task: &'a Task<M>,
+    window: Window,
+    max_condition: f64,
+) -> (Windows<'a, M, X::Error>, Conditioning) {
+    let count = window.count(task.matrices().len());
+    match invert_all(task, count, max_condition) {
+        Ok((inverses, condition)) => (
+            Box::new(transform(multiplier, task, window, inverses)),
+            Conditioning::Inverted(condition),
+        ),
+        Err(fallback) => (solver::stream_memoizing(multiplier, task, window), fallback),
+    }
+}
+
+/// Computes only the windows starting with `A[i]` for each `i` in `indices`, in that order,
+/// transforming the windows up to the last of them or falling back to [`solver::solve_only`].
+pub fn solve_only<M: Clone + Shape + Invert, X: Multiplier<M>>(
+    multiplier: &mut X,
+    task: &Task<M>,
+    window: Window,
+    max_condition: f64,
+    indices: &[usize],
+) -> Result<(Solution<M>, Conditioning), X::Error> {
+    let (windows, conditioning) = stream_only(multiplier, task, window, max_condition, indices);
+    Ok((Solution(windows.collect::<Result<_, _>>()?), conditioning))
+}
+
+/// Like [`solve_only`], but yields each window as soon as it is transformed,
+/// holding the ones which come too early only until their turn.
+pub fn stream_only<'a, M: Clone + Shape + Invert, X: Multiplier<M>>(
+    multiplier: &'a mut X,
+    task: &'a Task<M>,
+    window: Window,
+    max_condition: f64,
+    indices: &'a [usize],
+) -> (Windows<'a, M, X::Error>, Conditioning) {
+    let count = window.count(task.matrices().len());
+    assert!(
+        indices.iter().all(|&index| index < count),
+        "Window indices should be in 0..{count}"
+    );
+
+    let computed = indices.iter().max().map_or(0, |last| last + 1);
+    match invert_all(task, computed, max_condition) {
+        Ok((inverses, condition)) => (
+            Box::new(solver::select(
+                transform(multiplier, task, window, inverses),
+                indices,
+            )),
+            Conditioning::Inverted(condition),
+        ),
+        Err(fallback) => (
+            solver::stream_only(multiplier, task, window, indices),
+            fallback,
+        ),
+    }
+}
+
+/// Inverts the matrices transforming the first `count` windows,
+/// returning them along with the largest condition number.
+fn invert_all<M: Shape + Invert>(
+    task: &Task<M>,
+    count: usize,
+    max_condition: f64,
+) -> Result<(Vec<M>, f64), Conditioning> {
+    // The last window is not transformed any further.
+    let mut inverses = Vec::with_capacity(count.saturating_sub(1));
+    let mut max = 1_f64;
+    for (index, matrix) in task
+        .matrices()
+        .iter()
+        .enumerate()
+        .take(count.saturating_sub(1))
+    {
+        match matrix.invert() {
+            Some(Inverse { matrix, condition }) if condition <= max_condition => {
+                max = max.max(condition);
+                inverses.push(matrix);
+            }
+            inverse => {
+                return Err(Conditioning::Fallback {
+                    index,
+                    condition: inverse.map_or(f64::INFINITY, |inverse| inverse.condition),
+                })
+            }
+        }
+    }
+    Ok((inverses, max))
+}
+
+/// Computes the first window and transforms it by each of the inverses in turn,
+/// which yields one more window than there are inverses, or ends with the first failure.
+fn transform<'a, M: Clone + Shape, X: Multiplier<M>>(
+    multiplier: &'a mut X,
+    task: &'a Task<M>,
+    window: Window,
+    inverses: Vec<M>,
+) -> impl Iterator<Item = Result<M, X::Error>> + 'a {
+    let matrices = task.matrices();
+    let n = matrices.len();
+
+    let mut previous: Option<M> = None;
+    let mut inverses = inverses.into_iter().enumerate();
+    solver::until_failure(iter::from_fn(move || {
+        let next = match &previous {
+            None => multiplier
+                .multiply_all(solver::window_matrices(task, window, 0))
+                .map(|product| product.expect("Windows are not empty")),
+            Some(previous) => {
+                let (index, inverse) = inverses.next()?;
+                multiplier
+                    .multiply(&inverse, previous)
+                    .and_then(|transformed| {
+                        multiplier.multiply(&transformed, &matrices[(index + window.length) % n])
+                    })
+            }
+        };
+        if let Ok(next) = &next {
+            previous = Some(next.clone());
+        }
+        Some(next)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use super::*;
+    use crate::seq::{self, Cpu};
+
+    #[test]
+    fn inverts() {
+        let matrix = Matrix::from_vec(vec![0., 1., 2., 1., 0., 3., 4., -3., 8.]).unwrap();
+        let Inverse {
+            matrix: inverse, ..
+        } = matrix.invert().unwrap();
+        let identity = seq::multiply(&matrix, &inverse);
+        for (index, value) in identity.as_slice().iter().enumerate() {
+            let expected = if index % 4 == 0 { 1_f64 } else { 0. };
+            assert!((value - expected).abs() < 1e-12, "{identity:?}");
+        }
+
+        let singular = Matrix::from_vec(vec![1., 2., 2., 4.]).unwrap();
+        assert_eq!(singular.invert(), None);
+    }
+
+    #[test]
+    fn transforms_windows() {
+        let task = Task::chain(6, |i| [2., f64::from(i), -1., 1.]);
+
+        for window in [Window::full(&task), Window::cyclic(2), Window::linear(3)] {
+            let (solution, conditioning) = solve(&mut Cpu, &task, window, 1e3).unwrap();
+            assert!(matches!(conditioning, Conditioning::Inverted(_)));
+            for (actual, expected) in iter::zip(
+                &solution.0,
+                &solver::solve(&mut Cpu, &task, window).unwrap().0,
+            ) {
+                for (a, e) in iter::zip(actual.as_slice(), expected.as_slice()) {
+                    assert!((a - e).abs() <= 1e-9 * e.abs().max(1.), "{window:?}");
+                }
+            }
+
+            let (only, _) = solve_only(&mut Cpu, &task, window, 1e3, &[1, 0]).unwrap();
+            assert_eq!(only.0, [solution.0[1].clone(), solution.0[0].clone()]);
+        }
+
+        let (solution, conditioning) = solve(&mut Cpu, &task, Window::full(&task), 1.).unwrap();
+        assert!(matches!(
+            conditioning,
+            Conditioning::Fallback { index: 0, .. }
+        ));
+        assert_eq!(solution, seq::solve_memoizing(&task, Window::full(&task)));
+    }
+}
@@ -0,0 +1,439 @@
+//! Algorithms computing products of consecutive matrices on top of a back-end's multiplication.
+
+use std::{collections::BTreeMap, iter};
+
+use crate::{
+    semiring::Semiring,
+    task::{Matrix, Shape, Solution, Task, Window},
+};
+
+/// Multiplication of matrices represented as `M`.
+pub trait Multiplier<M> {
+    /// Why a multiplication failed, which is [`Infallible`](std::convert::Infallible) on the CPU.
+    type Error;
+
+    fn multiply(&mut self, a: &M, b: &M) -> Result<M, Self::Error>;
+
+    fn multiply_all(
+        &mut self,
+        mut matrices: impl Iterator<Item = M>,
+    ) -> Result<Option<M>, Self::Error> {
+        let Some(first) = matrices.next() else {
+            return Ok(None);
+        };
+        matrices
+            .try_fold(first, |l, r| self.multiply(&l, &r))
+            .map(Some)
+    }
+}
+
+/// Representation of matrices of `Value`s, whose products are computed from the matrices' ones.
+pub trait Representation: Sized {
+    type Value: Semiring;
+
+    /// Product of `a` and `b` given the product of their matrices by `multiply`.
+    fn product<E>(
+        a: &Self,
+        b: &Self,
+        multiply: impl FnOnce(
+            &Matrix<Self::Value>,
+            &Matrix<Self::Value>,
+        ) -> Result<Matrix<Self::Value>, E>,
+    ) -> Result<Self, E>;
+}
+
+impl<T: Semiring> Representation for Matrix<T> {
+    type Value = T;
+
+    fn product<E>(
+        a: &Self,
+        b: &Self,
+        multiply: impl FnOnce(&Self, &Self) -> Result<Self, E>,
+    ) -> Result<Self, E> {
+        multiply(a, b)
+    }
+}
+
+/// Windows yielded one by one, each of them or the failure to compute it.
+pub type Windows<'a, M, E> = Box<dyn Iterator<Item = Result<M, E>> + 'a>;
+
+/// Computes each of the windows independently using `length - 1` multiplications per window.
+pub fn solve<M: Clone + Shape, X: Multiplier<M>>(
+    multiplier: &mut X,
+    task: &Task<M>,
+    window: Window,
+) -> Result<Solution<M>, X::Error> {
+    stream(multiplier, task, window)
+        .collect::<Result<_, _>>()
+        .map(Solution)
+}
+
+/// Like [`solve`], but yields each window as soon as it is computed,
+/// so that it can be dropped before the next one is. Ends with the first failure.
+pub fn stream<'a, M: Clone + Shape, X: Multiplier<M>>(
+    multiplier: &'a mut X,
+    task: &'a Task<M>,
+    window: Window,
+) -> impl Iterator<Item = Result<M, X::Error>> + 'a {
+    let n = task.matrices().len();
+
+    until_failure((0..window.count(n)).map(move |index| {
+        let product = multiplier.multiply_all(window_matrices(task, window, index))?;
+        Ok(product.expect("This is unrechable when `n` is zero"))
+    }))
+}
+
+/// Matrices of the window starting with `A[index]`.
+pub fn window_matrices<M: Clone + Shape>(
+    task: &Task<M>,
+    window: Window,
+    index: usize,
+) -> impl Iterator<Item = M> + '_ {
+    task.matrices()
+        .iter()
+        .cycle()
+        .skip(index)
+        .take(window.length)
+        .cloned()
+}
+
+/// Computes the windows from shared partial products using about 3 multiplications per window.
+pub fn solve_memoizing<M: Clone + Shape, X: Multiplier<M>>(
+    multiplier: &mut X,
+    task: &Task<M>,
+    window: Window,
+) -> Result<Solution<M>, X::Error> {
+    stream_memoizing(multiplier, task, window)
+        .collect::<Result<_, _>>()
+        .map(Solution)
+}
+
+/// Like [`solve_memoizing`], but yields each window as soon as it is computed
+/// from the partial products, which are computed beforehand. Ends with the first failure,
+/// which is the only item if one of the partial products fails.
+pub fn stream_memoizing<'a, M: Clone + Shape, X: Multiplier<M>>(
+    multiplier: &'a mut X,
+    task: &'a Task<M>,
+    window: Window,
+) -> Windows<'a, M, X::Error> {
+    let windows: Result<Box<dyn Iterator<Item = _>>, _> = if window == Window::full(task) {
+        stream_cycle(multiplier, task).map(|windows| Box::new(until_failure(windows)) as _)
+    } else {
+        stream_blocks(multiplier, task, window).map(|windows| Box::new(until_failure(windows)) as _)
+    };
+    windows.unwrap_or_else(|error| Box::new(iter::once(Err(error))))
+}
+
+/// Computes only the windows starting with `A[i]` for each `i` in `indices`, in that order,
+/// either reducing each of them directly or selecting them from all the memoized ones,
+/// whichever takes fewer multiplications.
+pub fn solve_only<M: Clone + Shape, X: Multiplier<M>>(
+    multiplier: &mut X,
+    task: &Task<M>,
+    window: Window,
+    indices: &[usize],
+) -> Result<Solution<M>, X::Error> {
+    stream_only(multiplier, task, window, indices)
+        .collect::<Result<_, _>>()
+        .map(Solution)
+}
+
+/// Like [`solve_only`], but yields each window as soon as it is computed,
+/// holding the memoized ones only until their turn. Ends with the first failure.
+pub fn stream_only<'a, M: Clone + Shape, X: Multiplier<M>>(
+    multiplier: &'a mut X,
+    task: &'a Task<M>,
+    window: Window,
+    indices: &'a [usize],
+) -> Windows<'a, M, X::Error> {
+    let n = task.matrices().len();
+    let count = window.count(n);
+    assert!(
+        indices.iter().all(|&index| index < count),
+        "Window indices should be in 0..{count}"
+    );
+
+    if indices.len() * (window.length - 1) <= memoizing_cost(n, window) {
+        Box::new(until_failure(indices.iter().map(move |&index| {
+            let product = multiplier.multiply_all(window_matrices(task, window, index))?;
+            Ok(product.expect("Windows are not empty"))
+        })))
+    } else {
+        Box::new(select(stream_memoizing(multiplier, task, window), indices))
+    }
+}
+
+/// Picks the windows at `indices`, in that order, from all the windows in their order,
+/// holding each of the windows which come too early only until its turn.
+/// A failure is yielded as soon as it is reached, and ends the selected windows.
+pub fn select<'a, M: Clone + 'a, E: 'a>(
+    windows: impl Iterator<Item = Result<M, E>> + 'a,
+    indices: &'a [usize],
+) -> impl Iterator<Item = Result<M, E>> + 'a {
+    let mut windows = windows.enumerate();
+    let mut held = BTreeMap::new();
+    until_failure(indices.iter().enumerate().map(move |(position, &index)| {
+        let later = &indices[position + 1..];
+        if let Some(window) = held.get(&index) {
+            return Ok(if later.contains(&index) {
+                M::clone(window)
+            } else {
+                held.remove(&index).unwrap()
+            });
+        }
+
+        loop {
+            let (next, window) = windows.next().expect("Window indices should be in bounds");
+            let window = window?;
+            if next == index {
+                if later.contains(&index) {
+                    held.insert(index, window.clone());
+                }
+                return Ok(window);
+            }
+            if later.contains(&next) {
+                held.insert(next, window);
+            }
+        }
+    }))
+}
+
+/// Passes the windows through up to the first failure,
+/// without computing any of the ones after it.
+pub fn until_failure<M, E>(
+    mut windows: impl Iterator<Item = Result<M, E>>,
+) -> impl Iterator<Item = Result<M, E>> {
+    let mut failed = false;
+    iter::from_fn(move || {
+        if failed {
+            return None;
+        }
+        let window = windows.next()?;
+        failed = window.is_err();
+        Some(window)
+    })
+}
+
+/// The number of multiplications performed by [`solve_memoizing`].
+fn memoizing_cost(n: usize, window: Window) -> usize {
+    if window == Window::cyclic(n) {
+        return if n == 1 { 0 } else { 3 * n - 4 };
+    }
+
+    let count = window.count(n);
+    let len = count + window.length - 1;
+    let blocks = len.div_ceil(window.length);
+    // Suffixes and prefixes within the blocks, then the windows which are not whole blocks.
+    2 * (len - blocks) + count - count.div_ceil(window.length)
+}
+
+/// Computes the full cyclic products from prefix and suffix products using `3N - 4` multiplications.
+fn stream_cycle<'a, M: Clone + Shape, X: Multiplier<M>>(
+    multiplier: &'a mut X,
+    task: &'a Task<M>,
+) -> Result<impl Iterator<Item = Result<M, X::Error>> + 'a, X::Error> {
+    let n = task.matrices().len();
+    let first = task.matrices().first().expect("Tasks are not empty");
+
+    let mut left_muls = Vec::with_capacity(n);
+    left_muls.push(first.clone());
+    for index in 0..(n - 1) {
+        left_muls.push(multiplier.multiply(&left_muls[index], &task.matrices()[index + 1])?);
+    }
+
+    let last = task.matrices().last().unwrap();
+    let mut right_muls = Vec::with_capacity(n - 1);
+    if n > 1 {
+        right_muls.push(last.clone());
+        for index in 0..n - 2 {
+            right_muls
+                .push(multiplier.multiply(&task.matrices()[n - index - 2], &right_muls[index])?)
+        }
+    }
+
+    // Start with `left_muls[n-1]` which is actually `A[0] * ... * A[n-1]`,
+    //  then produce multiplications `right_muls[n-1 - (1..n)] * left_muls[1..n]`.
+    left_muls.rotate_right(1);
+    Ok(iter::zip(
+        left_muls,
+        [None]
+            .into_iter()
+            .chain(right_muls.into_iter().rev().map(Some)),
+    )
+    .map(move |(left, right)| {
+        if let Some(right) = right {
+            multiplier.multiply(&right, &left)
+        } else {
+            Ok(left)
+        }
+    }))
+}
+
+/// Splits the (unrolled) sequence into blocks of the window's length,
+/// so that each window is a suffix of one block times a prefix of the next one.
+fn stream_blocks<'a, M: Clone + Shape, X: Multiplier<M>>(
+    multiplier: &'a mut X,
+    task: &'a Task<M>,
+    window: Window,
+) -> Result<impl Iterator<Item = Result<M, X::Error>> + 'a, X::Error> {
+    let n = task.matrices().len();
+    let count = window.count(n);
+    let length = window.length;
+    // Cyclic windows wrap around, so the sequence continues with the first matrices.
+    let len = count + length - 1;
+    let at = |index: usize| &task.matrices()[index % n];
+
+    // `suffixes[i]` ends at the end of the `i`'s block, `prefixes[i]` starts at its beginning.
+    let mut suffixes = Vec::with_capacity(len);
+    let mut prefixes = Vec::with_capacity(len);
+    for start in (0..len).step_by(length) {
+        let end = (start + length).min(len);
+
+        let mut block = vec![at(end - 1).clone()];
+        for index in (start..end - 1).rev() {
+            block.push(multiplier.multiply(at(index), block.last().unwrap())?);
+        }
+        suffixes.extend(block.into_iter().rev());
+
+        prefixes.push(at(start).clone());
+        for index in start + 1..end {
+            prefixes.push(multiplier.multiply(prefixes.last().unwrap(), at(index))?);
+        }
+    }
+
+    Ok((0..count).map(move |start| {
+        if start % length == 0 {
+            Ok(suffixes[start].clone())
+        } else {
+            multiplier.multiply(&suffixes[start], &prefixes[start + length - 1])
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+    use crate::{
+        seq::{self, Cpu},
+        task::Matrix,
+    };
+
+    /// Counts multiplications instead of performing them.
+    struct Counter(usize);
+    impl Multiplier<Matrix<i32>> for Counter {
+        type Error = Infallible;
+
+        fn multiply(
+            &mut self,
+            a: &Matrix<i32>,
+            b: &Matrix<i32>,
+        ) -> Result<Matrix<i32>, Infallible> {
+            self.0 += 1;
+            Ok(seq::multiply(a, b))
+        }
+    }
+
+    #[test]
+    fn windows_match_direct() {
+        let task = Task::chain(7, |i| [i, 1, i * i, 2]);
+
+        for length in 1..=7 {
+            for window in [Window::cyclic(length), Window::linear(length)] {
+                assert_eq!(
+                    solve_memoizing(&mut Cpu, &task, window),
+                    solve(&mut Cpu, &task, window),
+                    "{window:?}"
+                );
+
+                let mut counter = Counter(0);
+                solve_memoizing(&mut counter, &task, window).unwrap();
+                assert_eq!(counter.0, memoizing_cost(7, window), "{window:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn streams_lazily() {
+        let task = Task::chain(6, |i| [i, 3, 1, i + 1]);
+        let window = Window::cyclic(4);
+        let all = solve(&mut Cpu, &task, window).unwrap();
+
+        let mut counter = Counter(0);
+        assert_eq!(
+            stream(&mut counter, &task, window).next(),
+            Some(Ok(all.0[0].clone()))
+        );
+        assert_eq!(counter.0, 3);
+
+        // The partial products are computed beforehand, then each window takes one more.
+        let mut counter = Counter(0);
+        drop(stream_memoizing(&mut counter, &task, window));
+        let precomputed = counter.0;
+        let mut windows = stream_memoizing(&mut counter, &task, window);
+        assert_eq!(windows.nth(1), Some(Ok(all.0[1].clone())));
+        drop(windows);
+        assert_eq!(counter.0, 2 * precomputed + 1);
+
+        let windows = (0..5).map(|i| Ok::<_, Infallible>(i * 10));
+        let selected: Result<Vec<_>, _> = select(windows, &[3, 1, 3, 0]).collect();
+        assert_eq!(selected, Ok(vec![30, 10, 30, 0]));
+    }
+
+    /// Performs as many multiplications as it counts, then fails the next ones.
+    struct Failing(usize);
+    impl Multiplier<Matrix<i32>> for Failing {
+        type Error = usize;
+
+        fn multiply(&mut self, a: &Matrix<i32>, b: &Matrix<i32>) -> Result<Matrix<i32>, usize> {
+            match self.0.checked_sub(1) {
+                Some(left) => {
+                    self.0 = left;
+                    Ok(seq::multiply(a, b))
+                }
+                None => Err(0),
+            }
+        }
+    }
+
+    #[test]
+    fn streams_end_with_failure() {
+        let task = Task::chain(5, |i| [i, 1, 2, i]);
+        let window = Window::linear(2);
+
+        let mut failing = Failing(2);
+        let windows: Vec<_> = stream(&mut failing, &task, window).collect();
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[2], Err(0));
+
+        let mut failing = Failing(0);
+        assert_eq!(
+            stream_memoizing(&mut failing, &task, window).collect::<Vec<_>>(),
+            vec![Err(0)]
+        );
+        let mut failing = Failing(0);
+        assert_eq!(
+            stream_only(&mut failing, &task, window, &[3, 1]).collect::<Vec<_>>(),
+            vec![Err(0)]
+        );
+        assert_eq!(solve(&mut Failing(3), &task, window), Err(0));
+    }
+
+    #[test]
+    fn only_selected() {
+        let task = Task::chain(9, |i| [i, 2, 1 - i, 1]);
+        let window = Window::full(&task);
+        let all = solve(&mut Cpu, &task, window).unwrap();
+
+        for indices in [vec![4], vec![8, 0, 4], (0..9).rev().collect()] {
+            assert_eq!(
+                solve_only(&mut Cpu, &task, window, &indices),
+                Ok(Solution(
+                    indices.iter().map(|&index| all.0[index].clone()).collect()
+                )),
+                "{indices:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Strassen–Winograd multiplication, which takes 7 half-sized products instead of 8
+//! at the cost of 15 additions and subtractions.
+
+use crate::{
+    semiring::{Ring, Semiring},
+    seq,
+    solver::{Multiplier, Representation},
+    task::Matrix,
+};
+
+/// Multiplier splitting the operands into quadrants until one of the dimensions
+/// does not exceed `cutoff`, after which the quadrants are multiplied by `inner`.
+///
+/// Sums of quadrants are computed on the host, and odd dimensions are padded with zeros.
+#[derive(Debug, Clone, Copy)]
+pub struct Strassen<X> {
+    pub inner: X,
+    pub cutoff: usize,
+}
+
+impl<M: Representation<Value: Ring>, X: Multiplier<Matrix<M::Value>>> Multiplier<M>
+    for Strassen<X>
+{
+    type Error = X::Error;
+
+    fn multiply(&mut self, a: &M, b: &M) -> Result<M, X::Error> {
+        M::product(a, b, |a, b| self.multiply_matrices(a, b))
+    }
+}
+
+impl<X> Strassen<X> {
+    fn multiply_matrices<T: Ring>(
+        &mut self,
+        a: &Matrix<T>,
+        b: &Matrix<T>,
+    ) -> Result<Matrix<T>, X::Error>
+    where
+        X: Multiplier<Matrix<T>>,
+    {
+        seq::assert_multipliable(a, b);
+        let (m, l, n) = (a.rows(), a.cols(), b.cols());
+        if m.min(l).min(n) <= self.cutoff.max(1) {
+            return self.inner.multiply(a, b);
+        }
+
+        let [m2, l2, n2] = [m, l, n].map(|d| d.div_ceil(2));
+        let quadrants = |matrix: &Matrix<T>, rows, cols| {
+            [(0, 0), (rows, 0), (0, cols), (rows, cols)]
+                .map(|(row, col)| block(matrix, row, col, rows, cols))
+        };
+        // Quadrants are numbered by row and column, e.g. `a21` is the lower left one.
+        let [a11, a21, a12, a22] = quadrants(a, m2, l2);
+        let [b11, b21, b12, b22] = quadrants(b, l2, n2);
+        let sub = |a: &Matrix<T>, b: &Matrix<T>| zip(a, b, |a, b| a.add(b.neg()));
+
+        let s1 = add(&a21, &a22);
+        let s2 = sub(&s1, &a11);
+        let s3 = sub(&a11, &a21);
+        let s4 = sub(&a12, &s2);
+        let t1 = sub(&b12, &b11);
+        let t2 = sub(&b22, &t1);
+        let t3 = sub(&b22, &b12);
+        let t4 = sub(&t2, &b21);
+
+        let p1 = self.multiply_matrices(&a11, &b11)?;
+        let p2 = self.multiply_matrices(&a12, &b21)?;
+        let p3 = self.multiply_matrices(&s4, &b22)?;
+        let p4 = self.multiply_matrices(&a22, &t4)?;
+        let p5 = self.multiply_matrices(&s1, &t1)?;
+        let p6 = self.multiply_matrices(&s2, &t2)?;
+        let p7 = self.multiply_matrices(&s3, &t3)?;
+
+        let u2 = add(&p1, &p6);
+        let u3 = add(&u2, &p7);
+        let c11 = add(&p1, &p2);
+        let c12 = add(&add(&u2, &p5), &p3);
+        let c21 = sub(&u3, &p4);
+        let c22 = add(&u3, &p5);
+
+        // The padding is cropped away.
+        let mut c = Matrix::zeros(m, n);
+        for (quadrant, row, col) in [(c11, 0, 0), (c21, m2, 0), (c12, 0, n2), (c22, m2, n2)] {
+            for r in 0..m2.min(m - row) {
+                for k in 0..n2.min(n - col) {
+                    c[(row + r, col + k)] = quadrant[(r, k)];
+                }
+            }
+        }
+        Ok(c)
+    }
+}
+
+/// Block of `rows × cols` entries starting at (`row`, `col`), padded with zeros.
+fn block<T: Semiring>(
+    matrix: &Matrix<T>,
+    row: usize,
+    col: usize,
+    rows: usize,
+    cols: usize,
+) -> Matrix<T> {
+    Matrix::from_fn(rows, cols, |r, c| {
+        let (r, c) = (row + r, col + c);
+        if r < matrix.rows() && c < matrix.cols() {
+            matrix[(r, c)]
+        } else {
+            T::ZERO
+        }
+    })
+}
+
+fn add<T: Semiring>(a: &Matrix<T>, b: &Matrix<T>) -> Matrix<T> {
+    zip(a, b, T::add)
+}
+
+/// Combines the entries in the layout of `a`, e.g. when the inner multiplier returns another one.
+fn zip<T: Semiring>(a: &Matrix<T>, b: &Matrix<T>, f: impl Fn(T, T) -> T) -> Matrix<T> {
+    let values = a
+        .as_slice()
+        .iter()
+        .zip(b.to_layout(a.layout()).as_slice())
+        .map(|(&a, &b)| f(a, b))
+        .collect();
+    Matrix::with_layout(a.rows(), a.cols(), a.layout(), values).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{blocked::Blocked, seq::tests::assert_matches_naive};
+
+    #[test]
+    fn matches_naive() {
+        let dimensions = [(1, 1, 1), (2, 2, 2), (5, 3, 7), (17, 16, 33), (40, 41, 39)];
+        for cutoff in [1, 4, 64] {
+            let mut strassen = Strassen {
+                inner: Blocked,
+                cutoff,
+            };
+            assert_matches_naive::<i32>(&dimensions, |a, b| {
+                let Ok(c) = strassen.multiply(a, b);
+                c
+            });
+        }
+    }
+}
@@ -1,92 +1,688 @@
-use std::ops::{Index, IndexMut};
+#[cfg(any(feature = "ndarray", feature = "nalgebra"))]
+mod interop;
 
-use crate::{types::Value, util::sqrt};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    iter,
+    ops::{Index, IndexMut},
+};
 
-/// Column-major matrix.
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct Matrix {
-    n: usize,
-    values: Box<[Value]>,
+use crate::{
+    semiring::Semiring,
+    types::{Scalar, Value},
+    util::sqrt,
+};
+
+/// Why values cannot form a matrix.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum MatrixError {
+    #[error("{len} values cannot form a square matrix")]
+    NotSquare { len: usize },
+    #[error("{len} values cannot form a {rows}x{cols} matrix")]
+    WrongLength {
+        rows: usize,
+        cols: usize,
+        len: usize,
+    },
 }
-impl Matrix {
-    pub const fn n(&self) -> usize {
-        self.n
+
+/// Why matrices cannot form a task.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum TaskError {
+    #[error("there should be at least one matrix")]
+    Empty,
+    /// `A[index]` does not have as many rows as the previous matrix has columns,
+    /// where the last matrix precedes the first one.
+    #[error(
+        "matrix {index} should have {expected} rows to follow the previous one, but has {found}"
+    )]
+    DimensionMismatch {
+        index: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Order in which the entries of a matrix are stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Layout {
+    /// Columns are contiguous, which is what the kernels work with.
+    #[default]
+    ColumnMajor,
+    /// Rows are contiguous, e.g. in CSV files and NumPy arrays by default.
+    RowMajor,
+}
+impl Layout {
+    /// The layout of the transpose stored in the same order.
+    pub const fn transposed(self) -> Self {
+        match self {
+            Self::ColumnMajor => Self::RowMajor,
+            Self::RowMajor => Self::ColumnMajor,
+        }
     }
+}
 
-    pub fn as_slice(&self) -> &[Value] {
-        self.values.as_ref()
+/// Matrix stored in either layout, column-major unless created otherwise.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "RawMatrix<T>")
+)]
+pub struct Matrix<T = Value> {
+    rows: usize,
+    cols: usize,
+    layout: Layout,
+    values: Box<[T]>,
+}
+
+/// Deserialized matrix whose values are yet to be checked against its dimensions.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "Matrix")]
+struct RawMatrix<T> {
+    rows: usize,
+    cols: usize,
+    #[serde(default)]
+    layout: Layout,
+    values: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<RawMatrix<T>> for Matrix<T> {
+    type Error = MatrixError;
+
+    fn try_from(raw: RawMatrix<T>) -> Result<Self, Self::Error> {
+        Self::with_layout(raw.rows, raw.cols, raw.layout, raw.values)
+    }
+}
+impl<T> Matrix<T> {
+    /// Creates a column-major matrix.
+    pub fn new(rows: usize, cols: usize, values: Vec<T>) -> Result<Self, MatrixError> {
+        Self::with_layout(rows, cols, Layout::ColumnMajor, values)
+    }
+
+    /// Creates a row-major matrix, taking the values as they are.
+    pub fn from_row_major(rows: usize, cols: usize, values: Vec<T>) -> Result<Self, MatrixError> {
+        Self::with_layout(rows, cols, Layout::RowMajor, values)
     }
 
-    pub fn from_vec(vec: Vec<Value>) -> Option<Self> {
-        let n = sqrt(vec.len());
-        if vec.len() == n * n {
-            Some(Self {
-                n,
-                values: vec.into_boxed_slice(),
+    pub fn with_layout(
+        rows: usize,
+        cols: usize,
+        layout: Layout,
+        values: Vec<T>,
+    ) -> Result<Self, MatrixError> {
+        if rows.checked_mul(cols) == Some(values.len()) {
+            Ok(Self {
+                rows,
+                cols,
+                layout,
+                values: values.into_boxed_slice(),
             })
         } else {
-            None
+            Err(MatrixError::WrongLength {
+                rows,
+                cols,
+                len: values.len(),
+            })
         }
     }
+
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub const fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub const fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// The entries in the order of the layout.
+    pub fn as_slice(&self) -> &[T] {
+        self.values.as_ref()
+    }
+
+    /// Creates a square column-major matrix.
+    pub fn from_vec(vec: Vec<T>) -> Result<Self, MatrixError> {
+        Self::try_from(vec.into_boxed_slice())
+    }
+
+    /// Creates a column-major matrix whose entry at (`row`, `col`) is `f(row, col)`.
+    pub fn from_fn(rows: usize, cols: usize, mut f: impl FnMut(usize, usize) -> T) -> Self {
+        let values = (0..cols)
+            .flat_map(|col| (0..rows).map(move |row| (row, col)))
+            .map(|(row, col)| f(row, col))
+            .collect();
+        Self::new(rows, cols, values).unwrap()
+    }
+
+    /// Creates a row-major matrix from its rows, which should all have the same length.
+    pub fn from_rows(
+        rows: impl IntoIterator<Item = impl IntoIterator<Item = T>>,
+    ) -> Result<Self, MatrixError> {
+        let (mut values, mut lengths) = (Vec::new(), Vec::new());
+        for row in rows {
+            let len = values.len();
+            values.extend(row);
+            lengths.push(values.len() - len);
+        }
+        let cols = lengths.first().copied().unwrap_or(0);
+        if lengths.iter().any(|&len| len != cols) {
+            return Err(MatrixError::WrongLength {
+                rows: lengths.len(),
+                cols,
+                len: values.len(),
+            });
+        }
+        Self::from_row_major(lengths.len(), cols, values)
+    }
+
+    /// Position of the entry at (`row`, `col`) in [`Self::as_slice`].
+    fn position(&self, row: usize, col: usize) -> usize {
+        assert!(
+            row < self.rows && col < self.cols,
+            "Entry ({row}, {col}) should be within {}x{}",
+            self.rows,
+            self.cols
+        );
+        match self.layout {
+            Layout::ColumnMajor => col * self.rows + row,
+            Layout::RowMajor => row * self.cols + col,
+        }
+    }
+
+    /// `len` entries starting at `start` which are `step` apart.
+    fn line(&self, start: usize, step: usize, len: usize) -> impl Iterator<Item = &T> {
+        let values = self.values.get(start..).unwrap_or_default();
+        values.iter().step_by(step.max(1)).take(len)
+    }
+
+    /// Entries of the row, from left to right.
+    pub fn row(&self, row: usize) -> impl Iterator<Item = &T> {
+        assert!(row < self.rows, "Row {row} should be in 0..{}", self.rows);
+        match self.layout {
+            Layout::ColumnMajor => self.line(row, self.rows, self.cols),
+            Layout::RowMajor => self.line(row * self.cols, 1, self.cols),
+        }
+    }
+
+    /// Entries of the column, from top to bottom.
+    pub fn col(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(
+            col < self.cols,
+            "Column {col} should be in 0..{}",
+            self.cols
+        );
+        match self.layout {
+            Layout::ColumnMajor => self.line(col * self.rows, 1, self.rows),
+            Layout::RowMajor => self.line(col, self.cols, self.rows),
+        }
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.rows).map(|row| self.row(row))
+    }
+
+    pub fn iter_cols(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.cols).map(|col| self.col(col))
+    }
+
+    /// Reinterprets the entries as the transpose stored in the other layout, which takes no copy.
+    pub fn into_transpose(self) -> Self {
+        Self {
+            rows: self.cols,
+            cols: self.rows,
+            layout: self.layout.transposed(),
+            values: self.values,
+        }
+    }
+
+    /// The transpose, stored in the other layout so that the entries are copied as they are.
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.clone().into_transpose()
+    }
+
+    /// The matrix stored in the layout, which is only copied if it is stored in the other one.
+    pub fn to_layout(&self, layout: Layout) -> Cow<'_, Self>
+    where
+        T: Clone,
+    {
+        if self.layout == layout {
+            return Cow::Borrowed(self);
+        }
+
+        let values = match layout {
+            Layout::ColumnMajor => self.iter_cols().flatten().cloned().collect(),
+            Layout::RowMajor => self.iter_rows().flatten().cloned().collect(),
+        };
+        Cow::Owned(Self::with_layout(self.rows, self.cols, layout, values).unwrap())
+    }
 }
-impl TryFrom<Box<[Value]>> for Matrix {
-    type Error = ();
+impl<T: Semiring> Matrix<T> {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self::new(rows, cols, vec![T::ZERO; rows * cols]).unwrap()
+    }
 
-    fn try_from(values: Box<[Value]>) -> Result<Self, Self::Error> {
+    pub fn identity(n: usize) -> Self {
+        Self::from_fn(n, n, |row, col| if row == col { T::ONE } else { T::ZERO })
+    }
+}
+impl<T: Scalar> Matrix<T> {
+    /// Whether the matrices have the same dimensions and each pair of their entries
+    /// is equal or differs by at most `tolerance · max(1, |a|, |b|)`.
+    pub fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        (self.rows, self.cols) == (other.rows, other.cols)
+            && iter::zip(self.iter_cols().flatten(), other.iter_cols().flatten()).all(|(&a, &b)| {
+                let (a, b) = (a.to_f64(), b.to_f64());
+                // Also covers infinities of tropical semirings.
+                a == b || (a - b).abs() <= tolerance * a.abs().max(b.abs()).max(1.)
+            })
+    }
+}
+impl<T> TryFrom<Box<[T]>> for Matrix<T> {
+    type Error = MatrixError;
+
+    fn try_from(values: Box<[T]>) -> Result<Self, Self::Error> {
         let n = sqrt(values.len());
         if values.len() == n * n {
-            Ok(Self { n, values })
+            Ok(Self {
+                rows: n,
+                cols: n,
+                layout: Layout::ColumnMajor,
+                values,
+            })
         } else {
-            Err(())
+            Err(MatrixError::NotSquare { len: values.len() })
         }
     }
 }
-impl TryFrom<Vec<Value>> for Matrix {
-    type Error = ();
+impl<T> TryFrom<Vec<T>> for Matrix<T> {
+    type Error = MatrixError;
 
-    fn try_from(value: Vec<Value>) -> Result<Self, Self::Error> {
-        Self::from_vec(value).ok_or(())
+    fn try_from(value: Vec<T>) -> Result<Self, Self::Error> {
+        Self::from_vec(value)
+    }
+}
+/// Matrices are equal if their entries are, whatever their layouts.
+impl<T: PartialEq> PartialEq for Matrix<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.rows, self.cols) == (other.rows, other.cols)
+            && if self.layout == other.layout {
+                self.values == other.values
+            } else {
+                iter::zip(self.iter_cols().flatten(), other.iter_cols().flatten())
+                    .all(|(a, b)| a == b)
+            }
+    }
+}
+/// Matrices are ordered by their dimensions and then by their entries
+/// in column-major order, whatever their layouts.
+impl<T: PartialOrd> PartialOrd for Matrix<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.rows, self.cols).cmp(&(other.rows, other.cols)) {
+            Ordering::Equal => self
+                .iter_cols()
+                .flatten()
+                .partial_cmp(other.iter_cols().flatten()),
+            ordering => Some(ordering),
+        }
     }
 }
-impl Index<usize> for Matrix {
-    type Output = Value;
+/// Indexing in the order of the layout.
+impl<T> Index<usize> for Matrix<T> {
+    type Output = T;
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.values[index]
     }
 }
-impl IndexMut<usize> for Matrix {
+impl<T> IndexMut<usize> for Matrix<T> {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         &mut self.values[index]
     }
 }
+/// Indexing by (`row`, `col`).
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        &self.values[self.position(row, col)]
+    }
+}
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        let position = self.position(row, col);
+        &mut self.values[position]
+    }
+}
+/// Rows on separate lines with right-aligned entries, which respect the precision if any.
+impl<T: Display> Display for Matrix<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let precision = f.precision();
+        let cells = Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            layout: self.layout,
+            values: self
+                .values
+                .iter()
+                .map(|value| match precision {
+                    Some(precision) => format!("{value:.precision$}"),
+                    None => value.to_string(),
+                })
+                .collect(),
+        };
+        let width = cells.values.iter().map(String::len).max().unwrap_or(0);
+        for (index, row) in cells.iter_rows().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            for (col, value) in row.enumerate() {
+                let separator = if col > 0 { "  " } else { "" };
+                write!(f, "{separator}{value:>width$}")?;
+            }
+        }
+        Ok(())
+    }
+}
+impl<T> Shape for Matrix<T> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
 
+    fn cols(&self) -> usize {
+        self.cols
+    }
+}
+
+/// Dimensions of a representation of a matrix.
+pub trait Shape {
+    fn rows(&self) -> usize;
+
+    fn cols(&self) -> usize;
+}
+
+/// Cyclic chain of matrices, where `A[i]` is `d[i] × d[i + 1]` and `d[N] = d[0]`.
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct Task {
-    n: usize,
-    matrices: Vec<Matrix>,
-}
-impl Task {
-    pub fn from_vec(matrices: Vec<Matrix>) -> Option<Self> {
-        let first = matrices.first()?;
-        let n = first.n();
-        for matrix in &matrices {
-            if matrix.n() != n {
-                return None;
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "RawTask<M>",
+        bound(deserialize = "M: Shape + serde::Deserialize<'de>")
+    )
+)]
+pub struct Task<M = Matrix> {
+    matrices: Vec<M>,
+}
+
+/// Deserialized task whose matrices are yet to be checked to form a chain.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "Task")]
+struct RawTask<M> {
+    matrices: Vec<M>,
+}
+
+#[cfg(feature = "serde")]
+impl<M: Shape> TryFrom<RawTask<M>> for Task<M> {
+    type Error = TaskError;
+
+    fn try_from(raw: RawTask<M>) -> Result<Self, Self::Error> {
+        Self::from_vec(raw.matrices)
+    }
+}
+impl<M: Shape> Task<M> {
+    pub fn from_vec(matrices: Vec<M>) -> Result<Self, TaskError> {
+        let last = matrices.last().ok_or(TaskError::Empty)?;
+        let mut cols = last.cols();
+        for (index, matrix) in matrices.iter().enumerate() {
+            if matrix.rows() != cols {
+                return Err(TaskError::DimensionMismatch {
+                    index,
+                    expected: cols,
+                    found: matrix.rows(),
+                });
             }
+            cols = matrix.cols();
         }
 
-        Some(Self { n, matrices })
+        Ok(Self { matrices })
     }
 
-    pub const fn n(&self) -> usize {
-        self.n
+    /// The largest dimension of the matrices.
+    pub fn max_dimension(&self) -> usize {
+        self.matrices
+            .iter()
+            .map(M::rows)
+            .max()
+            .expect("There is at least one matrix")
     }
 
-    pub fn matrices(&self) -> &[Matrix] {
+    pub fn matrices(&self) -> &[M] {
         self.matrices.as_slice()
     }
+
+    /// Replaces `A[index]` with a matrix of the same dimensions, returning the previous one.
+    pub fn replace(&mut self, index: usize, matrix: M) -> M {
+        assert_replaceable(&self.matrices[index], &matrix);
+        std::mem::replace(&mut self.matrices[index], matrix)
+    }
 }
 
+/// Checks that `matrix` has the dimensions of `previous`, so that it keeps the chain valid.
+pub fn assert_replaceable(previous: &impl Shape, matrix: &impl Shape) {
+    assert!(
+        (previous.rows(), previous.cols()) == (matrix.rows(), matrix.cols()),
+        "Matrix of dimensions {}x{} cannot replace one of dimensions {}x{}",
+        matrix.rows(),
+        matrix.cols(),
+        previous.rows(),
+        previous.cols()
+    );
+}
+
+#[cfg(test)]
+impl<T> Task<Matrix<T>> {
+    /// Chain of `k` 2x2 matrices, the `i`-th of which has the column-major `entries(i)`.
+    pub fn chain(k: usize, entries: impl FnMut(i32) -> [T; 4]) -> Self {
+        let matrices = (0..).take(k).map(entries);
+        Self::from_vec(
+            matrices
+                .map(|entries| Matrix::from_vec(entries.into()).unwrap())
+                .collect(),
+        )
+        .unwrap()
+    }
+}
+
+/// Products of consecutive matrices, where the `i`-th one starts with `A[i]`.
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct Solution(pub Vec<Matrix>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Solution<M = Matrix>(pub Vec<M>);
+
+/// Products of `length` consecutive matrices which are computed for a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Window {
+    pub length: usize,
+    /// Whether windows may wrap around from `A[N - 1]` to `A[0]`.
+    pub cyclic: bool,
+}
+impl Window {
+    /// Windows `A[i] · … · A[i + length - 1]` for each `i`, with indices taken modulo `N`.
+    pub const fn cyclic(length: usize) -> Self {
+        Self {
+            length,
+            cyclic: true,
+        }
+    }
+
+    /// Windows `A[i] · … · A[i + length - 1]` for each `i <= N - length`.
+    pub const fn linear(length: usize) -> Self {
+        Self {
+            length,
+            cyclic: false,
+        }
+    }
+
+    /// The original problem: `A[i] · … · A[N - 1] · A[0] · … · A[i - 1]` for each `i`.
+    pub fn full<M: Shape>(task: &Task<M>) -> Self {
+        Self::cyclic(task.matrices().len())
+    }
+
+    /// The number of windows over `n` matrices.
+    pub fn count(self, n: usize) -> usize {
+        assert!(
+            (1..=n).contains(&self.length),
+            "Window length {} should be in 1..={n}",
+            self.length
+        );
+        if self.cyclic {
+            n
+        } else {
+            n - self.length + 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matrix_api() {
+        let a = Matrix::from_rows([[1, 2, 3], [4, 5, 6]]).unwrap();
+        assert_eq!(a.as_slice(), [1, 2, 3, 4, 5, 6]);
+        assert_eq!((a[(0, 2)], a[(1, 0)]), (3, 4));
+        assert_eq!(a.row(1).copied().collect::<Vec<_>>(), [4, 5, 6]);
+        assert_eq!(
+            a.iter_cols()
+                .map(|col| col.copied().collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            [[1, 4], [2, 5], [3, 6]]
+        );
+        assert_eq!(
+            a.transpose(),
+            Matrix::from_fn(3, 2, |row, col| a[(col, row)])
+        );
+        assert_eq!(
+            Matrix::from_rows([vec![1, 2], vec![3]]),
+            Err(MatrixError::WrongLength {
+                rows: 2,
+                cols: 2,
+                len: 3
+            })
+        );
+
+        let identity = Matrix::identity(3);
+        assert_eq!(&a * &identity, a);
+        assert_eq!(&identity * &Matrix::zeros(3, 1), Matrix::zeros(3, 1));
+        assert_eq!(a.to_string(), "1  2  3\n4  5  6");
+
+        let b = Matrix::from_rows([[0.5, -10.], [1e-3, 2.]]).unwrap();
+        assert_eq!(format!("{b:.1}"), "  0.5  -10.0\n  0.0    2.0");
+        let c = Matrix::from_fn(2, 2, |row, col| b[(row, col)] * (1. + 1e-9));
+        assert!(b.approx_eq(&c, 1e-8));
+        assert!(!b.approx_eq(&c, 1e-10));
+        assert!(!b.approx_eq(&b.transpose(), 1e-8));
+    }
+
+    #[test]
+    fn layouts() {
+        let rows = Matrix::from_row_major(2, 3, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        let cols = Matrix::new(2, 3, vec![1, 4, 2, 5, 3, 6]).unwrap();
+        assert_eq!(rows, cols);
+        assert_eq!(rows.partial_cmp(&cols), Some(Ordering::Equal));
+        assert!(rows < Matrix::from_row_major(2, 3, vec![1, 2, 3, 5, 4, 6]).unwrap());
+        assert_eq!(
+            rows.to_layout(Layout::ColumnMajor).as_slice(),
+            cols.as_slice()
+        );
+        assert_eq!(cols.to_layout(Layout::RowMajor).as_slice(), rows.as_slice());
+        assert!(matches!(rows.to_layout(Layout::RowMajor), Cow::Borrowed(_)));
+
+        let transpose = rows.clone().into_transpose();
+        assert_eq!(transpose.layout(), Layout::ColumnMajor);
+        assert_eq!(transpose.as_slice(), rows.as_slice());
+        assert_eq!(
+            transpose,
+            Matrix::from_fn(3, 2, |row, col| cols[(col, row)])
+        );
+
+        let b = Matrix::from_row_major(3, 2, vec![1, 0, 2, -1, 0, 3]).unwrap();
+        let expected = &cols * &b.to_layout(Layout::ColumnMajor);
+        assert_eq!(&rows * &b, expected);
+        assert_eq!(&cols * &b, expected);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Matrix::<i32>::from_vec(vec![1, 2, 3]),
+            Err(MatrixError::NotSquare { len: 3 })
+        );
+
+        // The second matrix is followed by one with 5 rows instead of 4.
+        let chain = vec![
+            Matrix::zeros(2, 3),
+            Matrix::zeros(3, 4),
+            Matrix::<i32>::zeros(5, 2),
+        ];
+        assert_eq!(
+            Task::from_vec(chain),
+            Err(TaskError::DimensionMismatch {
+                index: 2,
+                expected: 4,
+                found: 5
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let a = Matrix::from_rows([[1., 2., 3.], [4., 5., 6.]]).unwrap();
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(
+            json,
+            r#"{"rows":2,"cols":3,"layout":"RowMajor","values":[1.0,2.0,3.0,4.0,5.0,6.0]}"#
+        );
+        let b: Matrix<f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!((b.layout(), &b), (Layout::RowMajor, &a));
+
+        let error = serde_json::from_str::<Matrix<f64>>(r#"{"rows":2,"cols":2,"values":[1.0]}"#)
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("cannot form a 2x2 matrix"),
+            "{error}"
+        );
+
+        let task = Task::from_vec(vec![a.clone(), a.transpose()]).unwrap();
+        let json = serde_json::to_string(&task).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Task<Matrix<f64>>>(&json).unwrap(),
+            task
+        );
+        let mismatched = Task {
+            matrices: vec![a.clone(), a],
+        };
+        let error = serde_json::to_string(&mismatched)
+            .and_then(|json| serde_json::from_str::<Task<Matrix<f64>>>(&json))
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("matrix 0 should have 3 rows"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Conversions between matrices and those of `ndarray` and `nalgebra`,
+//! which take the values as they are when the layouts match.
+
+use super::{Layout, Matrix};
+
+#[cfg(feature = "ndarray")]
+mod ndarray_impl {
+    use ndarray::{Array2, Array3, ArrayView2, ShapeBuilder};
+
+    use super::{Layout, Matrix};
+    use crate::task::{Task, TaskError};
+
+    /// Takes the values of contiguous arrays, either standard or Fortran ones, without reallocating.
+    ///
+    /// The values are shifted to the front of the buffer if the array starts past its beginning,
+    /// e.g. after slicing off leading rows, and copied if the array is not contiguous.
+    impl<T: Clone> From<Array2<T>> for Matrix<T> {
+        fn from(array: Array2<T>) -> Self {
+            let (rows, cols) = array.dim();
+            let layout = if array.is_standard_layout() {
+                Layout::RowMajor
+            } else if array.t().is_standard_layout() {
+                Layout::ColumnMajor
+            } else {
+                return Self::from(array.view());
+            };
+
+            // The entries of a contiguous array follow its offset in the order of its layout.
+            let (mut values, offset) = array.into_raw_vec_and_offset();
+            let offset = offset.unwrap_or_default();
+            values.truncate(offset + rows * cols);
+            values.drain(..offset);
+            Self::with_layout(rows, cols, layout, values).expect("Dimensions should match")
+        }
+    }
+
+    impl<T: Clone> From<ArrayView2<'_, T>> for Matrix<T> {
+        fn from(view: ArrayView2<'_, T>) -> Self {
+            let (rows, cols) = view.dim();
+            // Arrays are iterated in the logical order, i.e. row by row.
+            Self::from_row_major(rows, cols, view.iter().cloned().collect())
+                .expect("Dimensions should match")
+        }
+    }
+
+    impl<T> From<Matrix<T>> for Array2<T> {
+        fn from(matrix: Matrix<T>) -> Self {
+            let shape = (matrix.rows, matrix.cols);
+            let values = matrix.values.into_vec();
+            match matrix.layout {
+                Layout::RowMajor => Self::from_shape_vec(shape, values),
+                Layout::ColumnMajor => Self::from_shape_vec(shape.f(), values),
+            }
+            .expect("Dimensions should match")
+        }
+    }
+
+    impl<'a, T> From<&'a Matrix<T>> for ArrayView2<'a, T> {
+        fn from(matrix: &'a Matrix<T>) -> Self {
+            let shape = (matrix.rows, matrix.cols);
+            match matrix.layout {
+                Layout::RowMajor => Self::from_shape(shape, matrix.as_slice()),
+                Layout::ColumnMajor => Self::from_shape(shape.f(), matrix.as_slice()),
+            }
+            .expect("Dimensions should match")
+        }
+    }
+
+    /// Task of the `N` matrices along the first axis of an `N × n × n` array.
+    impl<T: Clone> TryFrom<Array3<T>> for Task<Matrix<T>> {
+        type Error = TaskError;
+
+        fn try_from(array: Array3<T>) -> Result<Self, Self::Error> {
+            Self::from_vec(array.outer_iter().map(Matrix::from).collect())
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_impl {
+    use nalgebra::{DMatrix, Scalar};
+
+    use super::{Layout, Matrix};
+
+    /// Takes the column-major values without copying.
+    impl<T: Scalar> From<DMatrix<T>> for Matrix<T> {
+        fn from(matrix: DMatrix<T>) -> Self {
+            let (rows, cols) = matrix.shape();
+            Self::new(rows, cols, matrix.data.into()).expect("Dimensions should match")
+        }
+    }
+
+    /// Takes the values without copying unless the matrix is row-major.
+    impl<T: Scalar> From<Matrix<T>> for DMatrix<T> {
+        fn from(matrix: Matrix<T>) -> Self {
+            let (rows, cols) = (matrix.rows, matrix.cols);
+            match matrix.layout {
+                Layout::ColumnMajor => Self::from_vec(rows, cols, matrix.values.into_vec()),
+                Layout::RowMajor => Self::from_row_slice(rows, cols, &matrix.values),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray() {
+        use ndarray::{array, s, Array2, Array3, ArrayView2};
+
+        use crate::task::{Task, TaskError};
+
+        let array = array![[1, 2, 3], [4, 5, 6]];
+        let copy = array.clone();
+        let pointer = copy.as_ptr();
+        let matrix = Matrix::from(copy);
+        assert_eq!(matrix, Matrix::from_rows([[1, 2, 3], [4, 5, 6]]).unwrap());
+        assert_eq!(
+            (matrix.layout(), matrix.as_slice().as_ptr()),
+            (Layout::RowMajor, pointer)
+        );
+        assert_eq!(Array2::from(matrix), array);
+
+        let transposed = Matrix::from(array.clone().reversed_axes());
+        assert_eq!(transposed.layout(), Layout::ColumnMajor);
+        assert_eq!(ArrayView2::from(&transposed), array.t());
+        let sliced = Matrix::from(array.slice(s![.., 1..]).to_owned());
+        assert_eq!(sliced, Matrix::from_rows([[2, 3], [5, 6]]).unwrap());
+        let strided = Matrix::from(array.slice(s![.., ..;2]));
+        assert_eq!(strided, Matrix::from_rows([[1, 3], [4, 6]]).unwrap());
+
+        let chain = Array3::from_shape_fn((3, 2, 2), |(i, r, c)| i * 4 + r * 2 + c);
+        let task = Task::try_from(chain).unwrap();
+        assert_eq!(
+            task.matrices()[2],
+            Matrix::from_rows([[8, 9], [10, 11]]).unwrap()
+        );
+        assert_eq!(
+            Task::try_from(Array3::<u8>::zeros((2, 2, 3))),
+            Err(TaskError::DimensionMismatch {
+                index: 0,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Task::try_from(Array3::<u8>::zeros((0, 2, 2))),
+            Err(TaskError::Empty)
+        );
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn nalgebra() {
+        use nalgebra::{dmatrix, DMatrix};
+
+        let dmatrix = dmatrix![1., 2., 3.; 4., 5., 6.];
+        let copy = dmatrix.clone();
+        let pointer = copy.as_ptr();
+        let matrix = Matrix::from(copy);
+        assert_eq!(
+            matrix,
+            Matrix::from_rows([[1., 2., 3.], [4., 5., 6.]]).unwrap()
+        );
+        assert_eq!(matrix.as_slice().as_ptr(), pointer);
+        assert_eq!(DMatrix::from(matrix.clone()), dmatrix);
+        assert_eq!(
+            DMatrix::from(matrix.to_layout(Layout::RowMajor).into_owned()),
+            dmatrix
+        );
+    }
+}
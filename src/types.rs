@@ -1,7 +1,186 @@
 //! Shared type definitions for fair comparison.
 
-use opencl3::types::cl_float;
+use std::fmt::{self, Debug, Display, Formatter};
 
+use opencl3::types::{cl_double, cl_float, cl_int, cl_long, cl_uint, cl_ulong};
+use rand::Rng;
+
+/// The default matrix element type.
 pub type Value = cl_float;
 
-pub const ZERO: Value = 0.;
+/// Matrix element type which can be stored both in host memory and in device buffers.
+///
+/// # Safety
+///
+/// The type should have exactly the same layout as [`Self::CL_TYPE`],
+/// since values are copied to and from device buffers as-is.
+pub unsafe trait Scalar:
+    Copy + Debug + Display + PartialEq + PartialOrd + Send + Sync + 'static
+{
+    /// Name of the corresponding OpenCL C type.
+    const CL_TYPE: &'static str;
+    /// OpenCL extension which should be supported by the device to use [`Self::CL_TYPE`].
+    const CL_EXTENSION: Option<&'static str> = None;
+    /// Whether the arithmetic is exact, so that results do not depend on the order of operations.
+    const EXACT: bool;
+
+    fn from_u8(value: u8) -> Self;
+
+    fn to_f64(self) -> f64;
+
+    /// Whether the value is neither NaN nor an infinity caused by overflow.
+    fn is_finite(self) -> bool {
+        true
+    }
+
+    /// Generates a random value for a benchmark task.
+    fn random(rng: &mut impl Rng) -> Self;
+}
+
+unsafe impl Scalar for cl_float {
+    const CL_TYPE: &'static str = "float";
+    const EXACT: bool = false;
+
+    fn from_u8(value: u8) -> Self {
+        value.into()
+    }
+
+    fn to_f64(self) -> f64 {
+        self.into()
+    }
+
+    fn is_finite(self) -> bool {
+        cl_float::is_finite(self)
+    }
+
+    fn random(rng: &mut impl Rng) -> Self {
+        -rng.gen_range(-1. ..0.)
+    }
+}
+
+unsafe impl Scalar for cl_double {
+    const CL_TYPE: &'static str = "double";
+    const CL_EXTENSION: Option<&'static str> = Some("cl_khr_fp64");
+    const EXACT: bool = false;
+
+    fn from_u8(value: u8) -> Self {
+        value.into()
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn is_finite(self) -> bool {
+        cl_double::is_finite(self)
+    }
+
+    fn random(rng: &mut impl Rng) -> Self {
+        -rng.gen_range(-1. ..0.)
+    }
+}
+
+// Signed overflow is undefined in OpenCL C, so the kernels operate on unsigned integers
+// which have the same representation and wrap the same way as two's complement ones.
+// Tropical semirings use signed types instead since they rely on the ordering.
+
+unsafe impl Scalar for cl_int {
+    const CL_TYPE: &'static str = "uint";
+    const EXACT: bool = true;
+
+    fn from_u8(value: u8) -> Self {
+        value.into()
+    }
+
+    fn to_f64(self) -> f64 {
+        self.into()
+    }
+
+    fn random(rng: &mut impl Rng) -> Self {
+        rng.gen_range(-9..=9)
+    }
+}
+
+unsafe impl Scalar for cl_long {
+    const CL_TYPE: &'static str = "ulong";
+    const EXACT: bool = true;
+
+    fn from_u8(value: u8) -> Self {
+        value.into()
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn random(rng: &mut impl Rng) -> Self {
+        rng.gen_range(-9..=9)
+    }
+}
+
+/// Integer modulo `P` which is expected to be prime.
+///
+/// Unlike floating-point values, the result of a product does not depend
+/// on the order of operations, so all solvers should produce identical results.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Modular<const P: cl_uint>(cl_uint);
+
+/// Integers modulo Mersenne prime `2^31 - 1`.
+pub type Mersenne31 = Modular<{ (1 << 31) - 1 }>;
+
+impl<const P: cl_uint> Modular<P> {
+    pub const fn new(value: cl_ulong) -> Self {
+        const { assert!(P > 1, "modulus should be greater than one") };
+        Self((value % P as cl_ulong) as cl_uint)
+    }
+
+    pub const fn get(self) -> cl_uint {
+        self.0
+    }
+}
+
+impl<const P: cl_uint> Display for Modular<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const P: cl_uint> serde::Serialize for Modular<P> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.0)
+    }
+}
+
+/// Only reduced values are accepted, so that equal integers have equal representations.
+#[cfg(feature = "serde")]
+impl<'de, const P: cl_uint> serde::Deserialize<'de> for Modular<P> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <cl_uint as serde::Deserialize>::deserialize(deserializer)?;
+        if value < P {
+            Ok(Self(value))
+        } else {
+            Err(serde::de::Error::custom(format_args!(
+                "{value} is not reduced modulo {P}"
+            )))
+        }
+    }
+}
+
+unsafe impl<const P: cl_uint> Scalar for Modular<P> {
+    const CL_TYPE: &'static str = "uint";
+    const EXACT: bool = true;
+
+    fn from_u8(value: u8) -> Self {
+        Self::new(value.into())
+    }
+
+    fn to_f64(self) -> f64 {
+        self.get().into()
+    }
+
+    fn random(rng: &mut impl Rng) -> Self {
+        Self(rng.gen_range(0..P))
+    }
+}